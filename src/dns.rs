@@ -0,0 +1,51 @@
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+/// The system's DNS resolver configuration, as read from `resolv.conf`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DnsConfig {
+    /// The nameservers to query, in order of preference. Any IPv6 zone index is dropped.
+    pub nameservers: Vec<IpAddr>,
+    /// The domains appended to names that are not fully qualified, in the order they are tried.
+    pub search: Vec<String>,
+    /// Resolver options such as `edns0` or `timeout:2`, as written.
+    pub options: Vec<String>,
+    /// Whether `resolv.conf` only pointed at the local systemd-resolved stub, so these values were
+    /// read from the uplink file that lists the servers systemd-resolved itself uses.
+    pub uses_systemd_resolved: bool,
+}
+
+/// Where to read the DNS resolver configuration from.
+///
+/// The defaults are the standard locations, so these only need to be changed to read another
+/// system's configuration or to test against temporary files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnsOptions {
+    pub(crate) resolv_conf_path: PathBuf,
+    pub(crate) resolved_uplink_path: PathBuf,
+}
+
+impl Default for DnsOptions {
+    fn default() -> Self {
+        Self {
+            resolv_conf_path: PathBuf::from("/etc/resolv.conf"),
+            resolved_uplink_path: PathBuf::from("/run/systemd/resolve/resolv.conf"),
+        }
+    }
+}
+
+impl DnsOptions {
+    /// Read the resolver configuration from this file instead of `/etc/resolv.conf`.
+    pub fn resolv_conf_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.resolv_conf_path = path.into();
+        self
+    }
+
+    /// Read the servers systemd-resolved forwards to from this file instead of
+    /// `/run/systemd/resolve/resolv.conf`. It is only used when `resolv.conf` names nothing but
+    /// the local stub.
+    pub fn resolved_uplink_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.resolved_uplink_path = path.into();
+        self
+    }
+}
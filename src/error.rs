@@ -4,10 +4,13 @@
 pub enum Error {
     CreateSocket(String),
     Bind(String),
+    SetSocketOption(String),
     CreatePipe(String),
     Getifaddrs(String),
     GetInterfaceName(String),
     FormatMacAddress,
+    InvalidHardwareAddress(String),
+    Netlink(String),
     UnexpectedWindowsResult(u32),
     AddressNotAssociated,
     InvalidParameter,
@@ -16,6 +19,7 @@ pub enum Error {
     NoAndroidContext,
     Jni(String),
     Io(std::io::Error),
+    Unsupported,
 }
 
 impl std::fmt::Display for Error {
@@ -0,0 +1,120 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::Error;
+
+/// A link-layer hardware address, such as an Ethernet MAC address.
+///
+/// Addresses of any length up to `HardwareAddr::MAX_LEN` bytes are supported, so longer
+/// addresses such as InfiniBand's 20-byte form are preserved. The address displays as
+/// colon-separated uppercase hex octets, e.g. `02:00:00:00:00:01`, and can be parsed from the
+/// same form or with `-` separators.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HardwareAddr {
+    len: u8,
+    // Bytes beyond `len` are always zero so that the derived comparisons are correct.
+    bytes: [u8; HardwareAddr::MAX_LEN],
+}
+
+impl HardwareAddr {
+    /// The longest address that can be represented, matching Linux's `MAX_ADDR_LEN`.
+    pub const MAX_LEN: usize = 32;
+
+    /// Create an address from its raw bytes.
+    ///
+    /// Returns `None` if `bytes` is empty or longer than `MAX_LEN`.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.is_empty() || bytes.len() > Self::MAX_LEN {
+            return None;
+        }
+        let mut addr = HardwareAddr {
+            len: bytes.len() as u8,
+            bytes: [0; Self::MAX_LEN],
+        };
+        addr.bytes[..bytes.len()].copy_from_slice(bytes);
+        Some(addr)
+    }
+
+    /// The raw bytes of the address.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+}
+
+impl fmt::Display for HardwareAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, b) in self.as_bytes().iter().enumerate() {
+            if i != 0 {
+                write!(f, ":")?;
+            }
+            write!(f, "{b:02X}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for HardwareAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HardwareAddr({self})")
+    }
+}
+
+impl FromStr for HardwareAddr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidHardwareAddress(s.to_string());
+        let mut bytes = Vec::with_capacity(Self::MAX_LEN);
+        for octet in s.split([':', '-']) {
+            if octet.len() != 2 {
+                return Err(invalid());
+            }
+            bytes.push(u8::from_str_radix(octet, 16).map_err(|_| invalid())?);
+        }
+        Self::from_bytes(&bytes).ok_or_else(invalid)
+    }
+}
+
+impl From<HardwareAddr> for String {
+    fn from(addr: HardwareAddr) -> Self {
+        addr.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mac_address_round_trips_through_a_string() {
+        let addr = HardwareAddr::from_bytes(&[0x02, 0, 0, 0, 0xab, 0x01]).unwrap();
+
+        assert_eq!(addr.to_string(), "02:00:00:00:AB:01");
+        assert_eq!("02:00:00:00:ab:01".parse::<HardwareAddr>().unwrap(), addr);
+        assert_eq!("02-00-00-00-AB-01".parse::<HardwareAddr>().unwrap(), addr);
+        assert_eq!(String::from(addr), "02:00:00:00:AB:01");
+    }
+
+    #[test]
+    fn long_addresses_keep_their_length() {
+        let bytes: Vec<u8> = (0..20).collect();
+        let addr = HardwareAddr::from_bytes(&bytes).unwrap();
+
+        assert_eq!(addr.as_bytes(), bytes.as_slice());
+        assert_eq!(addr.to_string().parse::<HardwareAddr>().unwrap(), addr);
+        assert_ne!(
+            addr,
+            HardwareAddr::from_bytes(&bytes[..6]).unwrap(),
+            "a prefix of an address is a different address"
+        );
+    }
+
+    #[test]
+    fn invalid_addresses_are_rejected() {
+        assert!(HardwareAddr::from_bytes(&[]).is_none());
+        assert!(HardwareAddr::from_bytes(&[0; 33]).is_none());
+        for invalid in ["", "2:00:00:00:00:01", "02:00:00:00:00:0g", "02::00"] {
+            assert!(invalid.parse::<HardwareAddr>().is_err(), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use nix::libc;
+
+/// A zeroed `ifreq` addressed to the named interface, or `None` if the name is too long.
+pub(crate) fn ifreq_for(name: &str) -> Option<libc::ifreq> {
+    let mut req: libc::ifreq = unsafe { std::mem::zeroed() };
+    // The name must fit with its nul terminator, which the zeroed request already provides.
+    if name.len() >= req.ifr_name.len() {
+        return None;
+    }
+    for (dst, src) in req.ifr_name.iter_mut().zip(name.bytes()) {
+        *dst = src as libc::c_char;
+    }
+    Some(req)
+}
@@ -0,0 +1,115 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// An item of a snapshot that is compared by key, so that a change to anything but the key is a
+/// modification of the same item rather than a removal and an addition.
+pub(crate) trait Keyed: Clone + Ord + Hash {
+    type Key: Eq + Hash;
+    type Update;
+
+    /// What identifies the item within a snapshot.
+    fn key(&self) -> Self::Key;
+
+    /// Build the update that reports `items` and how they changed.
+    fn update(is_initial: bool, items: Vec<Self>, diff: KeyedDiff<Self>) -> Self::Update;
+}
+
+/// What changed between two snapshots. Modified items are `(before, after)` pairs.
+pub(crate) struct KeyedDiff<T> {
+    pub(crate) added: Vec<T>,
+    pub(crate) removed: Vec<T>,
+    pub(crate) modified: Vec<(T, T)>,
+}
+
+/// A snapshot of keyed items, kept sorted so that equal snapshots compare equal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct KeyedList<T>(pub(crate) Vec<T>);
+
+impl<T> Default for KeyedList<T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T: Keyed> KeyedList<T> {
+    pub(crate) fn new(mut items: Vec<T>) -> Self {
+        items.sort();
+        items.dedup();
+        Self(items)
+    }
+
+    fn diff_from(&self, prev: &KeyedList<T>) -> KeyedDiff<T> {
+        let prev_set: HashSet<&T> = prev.0.iter().collect();
+        let curr_set: HashSet<&T> = self.0.iter().collect();
+        let mut added: Vec<T> = self
+            .0
+            .iter()
+            .filter(|item| !prev_set.contains(item))
+            .cloned()
+            .collect();
+        let mut removed: Vec<T> = prev
+            .0
+            .iter()
+            .filter(|item| !curr_set.contains(item))
+            .cloned()
+            .collect();
+
+        // An item is only considered modified when its key identifies it unambiguously on both
+        // sides. Otherwise it is left as a removal and an addition.
+        let mut key_counts: HashMap<T::Key, (usize, usize)> = HashMap::new();
+        for item in &added {
+            key_counts.entry(item.key()).or_default().0 += 1;
+        }
+        for item in &removed {
+            key_counts.entry(item.key()).or_default().1 += 1;
+        }
+        let is_modified = |item: &T| key_counts.get(&item.key()) == Some(&(1, 1));
+        let mut modified: Vec<(T, T)> = added
+            .iter()
+            .filter(|item| is_modified(item))
+            .map(|after| {
+                let before = removed
+                    .iter()
+                    .find(|before| before.key() == after.key())
+                    .unwrap();
+                (before.clone(), after.clone())
+            })
+            .collect();
+        added.retain(|item| !is_modified(item));
+        removed.retain(|item| !is_modified(item));
+        modified.sort_by(|a, b| a.1.cmp(&b.1));
+
+        KeyedDiff {
+            added,
+            removed,
+            modified,
+        }
+    }
+}
+
+/// Turns successive snapshots into updates, like `UpdateCursor` does for interfaces.
+pub(crate) struct KeyedCursor<T> {
+    prev_list: KeyedList<T>,
+    initial_pending: bool,
+}
+
+impl<T> Default for KeyedCursor<T> {
+    fn default() -> Self {
+        Self {
+            prev_list: KeyedList::default(),
+            initial_pending: true,
+        }
+    }
+}
+
+impl<T: Keyed> KeyedCursor<T> {
+    pub(crate) fn advance(&mut self, new_list: KeyedList<T>) -> Option<T::Update> {
+        if !self.initial_pending && new_list == self.prev_list {
+            return None;
+        }
+        let is_initial = std::mem::replace(&mut self.initial_pending, false);
+        let diff = new_list.diff_from(&self.prev_list);
+        self.prev_list = new_list.clone();
+        Some(T::update(is_initial, new_list.0, diff))
+    }
+}
@@ -111,11 +111,23 @@
 //! ```
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    time::{Duration, Instant},
 };
 
+mod dns;
 mod error;
+mod hw_addr;
+mod neighbour;
+mod primary;
+mod ra_dns;
+mod route;
+mod rule;
+mod stats;
+
+#[cfg(any(target_os = "linux", test))]
+mod keyed;
 
 #[cfg(any(windows, target_os = "android", test))]
 mod callback;
@@ -127,9 +139,40 @@ mod async_callback;
 mod watch_fd;
 
 #[cfg_attr(windows, path = "list_win.rs")]
-#[cfg_attr(unix, path = "list_unix.rs")]
+#[cfg_attr(target_os = "linux", path = "list_linux.rs")]
+#[cfg_attr(all(unix, not(target_os = "linux")), path = "list_unix.rs")]
 mod list;
 
+#[cfg(target_os = "linux")]
+mod netlink;
+
+#[cfg(target_os = "linux")]
+mod sysfs_linux;
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_vendor = "apple",
+    target_os = "freebsd"
+))]
+mod ifreq;
+
+#[cfg_attr(target_os = "linux", path = "routes_linux.rs")]
+#[cfg_attr(not(target_os = "linux"), path = "routes_unsupported.rs")]
+mod routes;
+
+#[cfg_attr(target_os = "linux", path = "resolv_linux.rs")]
+#[cfg_attr(not(target_os = "linux"), path = "resolv_unsupported.rs")]
+mod resolv;
+
+#[cfg_attr(target_os = "linux", path = "neighbours_linux.rs")]
+#[cfg_attr(not(target_os = "linux"), path = "neighbours_unsupported.rs")]
+mod neighbours;
+
+#[cfg_attr(target_os = "linux", path = "ra_watch_linux.rs")]
+#[cfg_attr(not(target_os = "linux"), path = "ra_watch_unsupported.rs")]
+mod ra_watch;
+
 #[cfg(target_os = "android")]
 mod android;
 
@@ -146,7 +189,17 @@ pub mod async_adapter;
 
 type IfIndex = u32;
 
+pub use dns::{DnsConfig, DnsOptions};
 pub use error::Error;
+pub use hw_addr::HardwareAddr;
+pub use neighbour::{ModifiedNeighbour, Neighbour, NeighbourDiff, NeighbourState, NeighbourUpdate};
+pub use primary::{PrimaryPath, PrimaryPathUpdate, PrimaryPaths};
+pub use ra_dns::{RaDnsConfig, RaDnsServer, RaDnsUpdate, RaLifetime, RaSearchDomain};
+pub use route::{
+    ModifiedRoute, NextHop, Route, RouteDiff, RouteLookup, RouteProtocol, RouteType, RouteUpdate,
+};
+pub use rule::{ModifiedRule, Rule, RuleAction, RuleDiff, RuleUpdate};
+pub use stats::{InterfaceRates, InterfaceStats};
 
 #[cfg(target_os = "android")]
 pub use android::set_android_context;
@@ -158,17 +211,240 @@ pub struct IpRecord {
     pub prefix_len: u8,
 }
 
+/// Flags describing the state of an address. Only reported on Linux.
+///
+/// These mirror the kernel's `IFA_F_*` flags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct AddrFlags {
+    /// An IPv4 address that is not the primary address for its subnet.
+    pub secondary: bool,
+    /// An IPv6 privacy address (RFC 8981).
+    pub temporary: bool,
+    /// Duplicate address detection has not finished. The address cannot be used yet.
+    pub tentative: bool,
+    /// Duplicate address detection is still running, but the address may already be used.
+    pub optimistic: bool,
+    /// Duplicate address detection found another node using this address.
+    pub dad_failed: bool,
+    /// The preferred lifetime has expired. Existing connections continue to work, but the
+    /// address should not be used for new ones.
+    pub deprecated: bool,
+    /// The address was configured statically rather than learned, e.g. from SLAAC or DHCP.
+    pub permanent: bool,
+}
+
+/// How widely an address is usable, from the kernel's `RT_SCOPE_*` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddrScope {
+    /// Usable anywhere (`RT_SCOPE_UNIVERSE`).
+    Global,
+    /// Usable within a site (`RT_SCOPE_SITE`).
+    Site,
+    /// Usable only on the attached link, e.g. an IPv6 link-local address.
+    Link,
+    /// Usable only on this host, e.g. a loopback address.
+    Host,
+    /// The address does not exist (`RT_SCOPE_NOWHERE`).
+    Nowhere,
+    /// A scope value with no dedicated variant.
+    Other(u8),
+}
+
+/// How long an address remains usable, as reported by the kernel. Only reported on Linux.
+///
+/// Lifetimes count down from `reported_at`, so a later report of the same address usually has
+/// shorter lifetimes and compares unequal. See `WatchOptions::ignore_lifetime_changes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddrLifetimes {
+    /// How long the address remains valid. `None` if it does not expire.
+    pub valid: Option<Duration>,
+    /// How long the address remains preferred for new connections. `None` if it does not expire.
+    pub preferred: Option<Duration>,
+    /// When these lifetimes were read from the kernel.
+    pub reported_at: Instant,
+}
+
+impl AddrLifetimes {
+    /// When the address will be removed, or `None` if it does not expire.
+    pub fn valid_until(&self) -> Option<Instant> {
+        self.valid.map(|valid| self.reported_at + valid)
+    }
+
+    /// When the address will become deprecated, or `None` if it does not expire.
+    pub fn preferred_until(&self) -> Option<Instant> {
+        self.preferred.map(|preferred| self.reported_at + preferred)
+    }
+}
+
+/// Extra information about an address, beyond its IP and prefix length.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AddrInfo {
+    /// Address state flags.
+    pub flags: AddrFlags,
+    /// Valid and preferred lifetimes. `None` if the address never expires, or if the platform
+    /// does not report lifetimes.
+    pub lifetimes: Option<AddrLifetimes>,
+    /// The broadcast address of the subnet, for IPv4 addresses on broadcast-capable links.
+    /// Available on Unix platforms.
+    pub broadcast: Option<IpAddr>,
+    /// The addresses of the remote ends, for addresses on point-to-point links, in ascending
+    /// order. The same local address can be configured once for each of several peers. Available
+    /// on Unix platforms.
+    pub peers: Vec<IpAddr>,
+    /// The scope of the address. Available on Linux.
+    pub scope: Option<AddrScope>,
+    /// The label of an IPv4 address, e.g. `eth0:1` for an alias. Available on Linux.
+    pub label: Option<String>,
+}
+
+impl AddrInfo {
+    fn same_ignoring_lifetimes(&self, other: &AddrInfo) -> bool {
+        // Destructured so that a field added to AddrInfo cannot be forgotten here.
+        let AddrInfo {
+            flags,
+            lifetimes: _,
+            broadcast,
+            peers,
+            scope,
+            label,
+        } = self;
+        *flags == other.flags
+            && *broadcast == other.broadcast
+            && *peers == other.peers
+            && *scope == other.scope
+            && *label == other.label
+    }
+}
+
+/// Link-layer flags of an interface.
+///
+/// These mirror the `IFF_*` flags on Unix platforms. On Windows they are derived from the
+/// adapter's type and operational status.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct LinkFlags {
+    /// The interface is administratively up.
+    pub up: bool,
+    /// The interface is operationally running.
+    pub running: bool,
+    /// The driver reports that the physical layer is up. Only reported on Linux.
+    pub lower_up: bool,
+    /// The interface is a loopback interface.
+    pub loopback: bool,
+    /// The interface is a point-to-point link.
+    pub point_to_point: bool,
+    /// The interface supports multicast.
+    pub multicast: bool,
+    /// The interface supports broadcast.
+    pub broadcast: bool,
+}
+
+/// Operational state of an interface, as defined by RFC 2863.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperState {
+    Unknown,
+    NotPresent,
+    Down,
+    LowerLayerDown,
+    Testing,
+    Dormant,
+    Up,
+}
+
+/// The type of a network interface.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum InterfaceKind {
+    Ethernet,
+    Loopback,
+    Bridge,
+    Veth,
+    Vlan,
+    /// A tun or tap device.
+    Tun,
+    Wireguard,
+    Bond,
+    /// A link kind without a dedicated variant, as named by the kernel, e.g. `"macvlan"`.
+    Other(String),
+    /// A device with no link kind and a hardware type without a dedicated variant, given as its
+    /// `ARPHRD_*` value, e.g. 32 for InfiniBand.
+    Arphrd(u16),
+}
+
+/// The duplex mode of a link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Duplex {
+    Half,
+    Full,
+}
+
+/// Physical link and driver details for one interface, as returned by `link_details`.
+///
+/// Each field is `None` when it is unknown. Virtual devices such as veths, bridges and dummies
+/// have no underlying hardware, so every field is `None` for them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkDetails {
+    /// Negotiated speed in megabits per second.
+    pub speed_mbps: Option<u32>,
+    /// Negotiated duplex mode.
+    pub duplex: Option<Duplex>,
+    /// Whether autonegotiation is enabled.
+    pub autoneg: Option<bool>,
+    /// Name of the kernel driver, e.g. `e1000e`.
+    pub driver: Option<String>,
+    /// Name of the underlying device on its bus, e.g. the PCI address `0000:00:1f.6`.
+    pub bus_info: Option<String>,
+}
+
 /// Information about one network interface at a point in time.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Interface {
     /// Internal index identifying this interface.
     pub index: u32,
     /// Interface name.
     pub name: String,
-    /// Hardware address. Android may have a placeholder due to privacy restrictions.
-    pub hw_addr: String,
+    /// Alternative names that also identify this interface. Available on Linux.
+    pub alt_names: Vec<String>,
+    /// A free-form description set by an administrator, e.g. with `ip link set ... alias`.
+    /// Available on Linux.
+    pub alias: Option<String>,
+    /// Hardware address, if the interface has one. Android may report a placeholder due to
+    /// privacy restrictions.
+    pub hw_addr: Option<HardwareAddr>,
+    /// The hardware address the device was manufactured with, if it is known and the device has
+    /// one. Available on Linux.
+    ///
+    /// This stays the same when `hw_addr` is changed or randomised. Virtual devices such as veths
+    /// have no permanent address.
+    pub perm_hw_addr: Option<HardwareAddr>,
     /// List of associated IPs and prefix length (netmask).
     pub ips: Vec<IpRecord>,
+    /// Extra information about each address in `ips`, where the platform provides it.
+    /// Available on Linux.
+    pub addr_info: BTreeMap<IpRecord, AddrInfo>,
+    /// Link-layer flags.
+    pub flags: LinkFlags,
+    /// Operational state. Available on Linux and Windows.
+    pub oper_state: Option<OperState>,
+    /// Whether the link has carrier, e.g. whether a cable is plugged in. Available on Linux.
+    pub carrier: Option<bool>,
+    /// Maximum transmission unit in bytes. Available on Linux, Android, Windows, macOS and
+    /// FreeBSD.
+    pub mtu: Option<u32>,
+    /// What type of interface this is. Available on Linux and Android.
+    ///
+    /// On Android only the hardware type is known, so virtual devices such as bridges and veths
+    /// are reported as `Ethernet`.
+    pub kind: Option<InterfaceKind>,
+    /// Index of the bridge or bond that this interface is enslaved to. Available on Linux.
+    pub master: Option<u32>,
+    /// Index of the device this interface is layered on, such as the parent of a VLAN or
+    /// macvlan, or the peer of a veth. Available on Linux.
+    ///
+    /// When `link_netnsid` is set, the index belongs to that network namespace rather than the
+    /// current one.
+    pub link: Option<u32>,
+    /// The id of the network namespace that `link` lives in, if it is not the current
+    /// namespace. Available on Linux.
+    pub link_netnsid: Option<i32>,
 }
 
 impl Interface {
@@ -188,6 +464,20 @@ impl Interface {
         })
     }
 
+    /// Whether the interface is using a hardware address other than its permanent one, e.g.
+    /// because the MAC address was randomised.
+    ///
+    /// This is `false` if the permanent address is not known.
+    pub fn has_non_permanent_hw_addr(&self) -> bool {
+        self.perm_hw_addr
+            .is_some_and(|perm_hw_addr| self.hw_addr != Some(perm_hw_addr))
+    }
+
+    /// Whether `name` is this interface's name or one of its alternative names.
+    pub fn has_name(&self, name: &str) -> bool {
+        self.name == name || self.alt_names.iter().any(|alt_name| alt_name == name)
+    }
+
     /// Canonicalise the address list so that vector equality is equivalent to
     /// set equality.
     ///
@@ -200,6 +490,51 @@ impl Interface {
         self.ips.sort();
         self.ips.dedup();
     }
+
+    /// Whether nothing but address lifetimes differ between the two interfaces.
+    fn same_ignoring_lifetimes(&self, other: &Interface) -> bool {
+        // Destructured so that a field added to Interface cannot be forgotten here.
+        let Interface {
+            index,
+            name,
+            alt_names,
+            alias,
+            hw_addr,
+            perm_hw_addr,
+            ips,
+            addr_info,
+            flags,
+            oper_state,
+            carrier,
+            mtu,
+            kind,
+            master,
+            link,
+            link_netnsid,
+        } = self;
+        *index == other.index
+            && *name == other.name
+            && *alt_names == other.alt_names
+            && *alias == other.alias
+            && *hw_addr == other.hw_addr
+            && *perm_hw_addr == other.perm_hw_addr
+            && *ips == other.ips
+            && addr_info.len() == other.addr_info.len()
+            && addr_info.iter().all(|(addr, info)| {
+                other
+                    .addr_info
+                    .get(addr)
+                    .is_some_and(|other_info| info.same_ignoring_lifetimes(other_info))
+            })
+            && *flags == other.flags
+            && *oper_state == other.oper_state
+            && *carrier == other.carrier
+            && *mtu == other.mtu
+            && *kind == other.kind
+            && *master == other.master
+            && *link == other.link
+            && *link_netnsid == other.link_netnsid
+    }
 }
 
 /// Information delivered when a network interface snapshot changes.
@@ -215,6 +550,10 @@ pub struct Update {
     pub interfaces: HashMap<IfIndex, Interface>,
     /// The changes from the preceding snapshot to `interfaces`.
     pub diff: UpdateDiff,
+    /// The current DNS resolver configuration, if the watch was created with
+    /// `WatchOptions::watch_dns`. An empty configuration is reported while `resolv.conf` cannot
+    /// be read.
+    pub dns: Option<DnsConfig>,
 }
 
 impl Update {
@@ -257,6 +596,15 @@ impl Update {
 
         from_removed_interfaces.chain(from_modified_interfaces)
     }
+
+    /// Iterate over every address whose `AddrInfo` changed in this update, while the address
+    /// itself stayed on the same interface. Each item is `(ifindex, &IpRecord)`.
+    pub fn addrs_modified(&self) -> impl Iterator<Item = (IfIndex, &IpRecord)> + '_ {
+        self.diff
+            .modified
+            .iter()
+            .flat_map(|(&idx, diff)| diff.addrs_modified.iter().map(move |addr| (idx, addr)))
+    }
 }
 
 /// What changed between one `Update` and the next.
@@ -268,6 +616,9 @@ pub struct UpdateDiff {
     pub removed: HashMap<IfIndex, Interface>,
     /// Changes to interfaces that were present in both snapshots.
     pub modified: HashMap<IfIndex, InterfaceDiff>,
+    /// Whether `Update::dns` changed. An update may carry only a DNS change, with no interface
+    /// changes at all.
+    pub dns_changed: bool,
 }
 
 /// What changed within a single interface between updates, if it was present in both.
@@ -275,27 +626,77 @@ pub struct UpdateDiff {
 pub struct InterfaceDiff {
     /// Whether the interface name changed.
     pub name_changed: bool,
-    /// Whether the hardware address changed.
+    /// Whether any alternative names were added or removed.
+    pub alt_names_changed: bool,
+    /// Whether the alias was set, changed or cleared.
+    ///
+    /// Linux only announces alternative name and alias changes for interfaces that are up. For
+    /// an interface that is down they are reported along with its next announced change.
+    pub alias_changed: bool,
+    /// Whether the current or permanent hardware address changed.
     pub hw_addr_changed: bool,
+    /// Whether any of the link flags changed.
+    pub flags_changed: bool,
+    /// Whether the interface was administratively brought up or down.
+    ///
+    /// Interfaces that are down only appear in snapshots when `include_down` is enabled, so
+    /// otherwise this transition is reported as an added or removed interface.
+    pub admin_state_changed: bool,
+    /// Whether the operational state or carrier changed.
+    pub oper_state_changed: bool,
+    /// Whether the MTU changed.
+    pub mtu_changed: bool,
+    /// Whether the interface was added to, removed from or moved between bridges or bonds.
+    pub master_changed: bool,
+    /// Whether the lower device or its namespace changed.
+    pub link_changed: bool,
     /// Addresses that appeared on this interface.
     pub addrs_added: Vec<IpRecord>,
     /// Addresses that disappeared from this interface.
     pub addrs_removed: Vec<IpRecord>,
+    /// Addresses that remain on this interface but whose `AddrInfo` changed, e.g. because
+    /// duplicate address detection finished.
+    pub addrs_modified: Vec<IpRecord>,
 }
 
 #[derive(Default, PartialEq, Eq, Clone)]
 struct List(HashMap<IfIndex, Interface>);
 
 impl List {
+    /// Platform listers include interfaces that are administratively down. Drop them unless
+    /// the caller asked to see them.
+    fn visible(mut self, include_down: bool) -> List {
+        if !include_down {
+            self.0.retain(|_, interface| interface.flags.up);
+        }
+        self
+    }
+
     fn initial_update(&self) -> Update {
-        self.update_from_with_flag(&List::default(), true)
+        self.update_from_with_flags(&List::default(), true, false)
     }
 
+    #[cfg(any(target_os = "android", test))]
     fn update_from(&self, prev: &List) -> Update {
-        self.update_from_with_flag(prev, false)
+        self.update_from_with_flags(prev, false, false)
     }
 
-    fn update_from_with_flag(&self, prev: &List, is_initial: bool) -> Update {
+    fn same_ignoring_lifetimes(&self, other: &List) -> bool {
+        self.0.len() == other.0.len()
+            && self.0.iter().all(|(index, interface)| {
+                other
+                    .0
+                    .get(index)
+                    .is_some_and(|other| interface.same_ignoring_lifetimes(other))
+            })
+    }
+
+    fn update_from_with_flags(
+        &self,
+        prev: &List,
+        is_initial: bool,
+        ignore_lifetimes: bool,
+    ) -> Update {
         let added = self
             .0
             .iter()
@@ -313,7 +714,12 @@ impl List {
             let Some(prev_interface) = prev.0.get(&index) else {
                 continue;
             };
-            if prev_interface == interface {
+            let unchanged = if ignore_lifetimes {
+                prev_interface.same_ignoring_lifetimes(interface)
+            } else {
+                prev_interface == interface
+            };
+            if unchanged {
                 continue;
             }
             let (addrs_added, addrs_removed) = if prev_interface.ips == interface.ips {
@@ -331,15 +737,54 @@ impl List {
                     .collect();
                 (addrs_added, addrs_removed)
             };
+            let addrs_modified = interface
+                .ips
+                .iter()
+                .filter(|addr| {
+                    if !prev_interface.ips.contains(addr) {
+                        return false;
+                    }
+                    match (
+                        prev_interface.addr_info.get(addr),
+                        interface.addr_info.get(addr),
+                    ) {
+                        (Some(prev_info), Some(info)) if ignore_lifetimes => {
+                            !prev_info.same_ignoring_lifetimes(info)
+                        }
+                        (prev_info, info) => prev_info != info,
+                    }
+                })
+                .cloned()
+                .collect();
             let name_changed = prev_interface.name != interface.name;
-            let hw_addr_changed = prev_interface.hw_addr != interface.hw_addr;
+            let alt_names_changed = prev_interface.alt_names != interface.alt_names;
+            let alias_changed = prev_interface.alias != interface.alias;
+            let hw_addr_changed = prev_interface.hw_addr != interface.hw_addr
+                || prev_interface.perm_hw_addr != interface.perm_hw_addr;
+            let flags_changed = prev_interface.flags != interface.flags;
+            let admin_state_changed = prev_interface.flags.up != interface.flags.up;
+            let oper_state_changed = prev_interface.oper_state != interface.oper_state
+                || prev_interface.carrier != interface.carrier;
+            let mtu_changed = prev_interface.mtu != interface.mtu;
+            let master_changed = prev_interface.master != interface.master;
+            let link_changed = prev_interface.link != interface.link
+                || prev_interface.link_netnsid != interface.link_netnsid;
             modified.insert(
                 index,
                 InterfaceDiff {
                     name_changed,
+                    alt_names_changed,
+                    alias_changed,
                     hw_addr_changed,
+                    flags_changed,
+                    admin_state_changed,
+                    oper_state_changed,
+                    mtu_changed,
+                    master_changed,
+                    link_changed,
                     addrs_added,
                     addrs_removed,
+                    addrs_modified,
                 },
             );
         }
@@ -350,43 +795,189 @@ impl List {
                 added,
                 removed,
                 modified,
+                dns_changed: false,
             },
+            dns: None,
         }
     }
 }
 
 struct UpdateCursor {
     prev_list: List,
+    prev_dns: Option<DnsConfig>,
     initial_pending: bool,
+    include_down: bool,
+    ignore_lifetime_changes: bool,
 }
 
 impl Default for UpdateCursor {
     fn default() -> Self {
         Self {
             prev_list: List::default(),
+            prev_dns: None,
             initial_pending: true,
+            include_down: false,
+            ignore_lifetime_changes: false,
         }
     }
 }
 
 impl UpdateCursor {
+    fn new(options: &WatchOptions) -> Self {
+        Self {
+            include_down: options.include_down,
+            ignore_lifetime_changes: options.ignore_lifetime_changes,
+            ..Default::default()
+        }
+    }
+
     fn advance(&mut self, new_list: List) -> Option<Update> {
+        self.advance_with_dns(new_list, None)
+    }
+
+    fn advance_with_dns(&mut self, new_list: List, dns: Option<DnsConfig>) -> Option<Update> {
+        let new_list = new_list.visible(self.include_down);
         if self.initial_pending {
             self.initial_pending = false;
-            self.prev_list = new_list.clone();
-            return Some(new_list.initial_update());
+            let mut update = new_list.initial_update();
+            update.diff.dns_changed = dns.is_some();
+            update.dns = dns.clone();
+            self.prev_list = new_list;
+            self.prev_dns = dns;
+            return Some(update);
         }
 
-        if new_list == self.prev_list {
+        let unchanged = if self.ignore_lifetime_changes {
+            new_list.same_ignoring_lifetimes(&self.prev_list)
+        } else {
+            new_list == self.prev_list
+        };
+        let dns_changed = dns != self.prev_dns;
+        if unchanged && !dns_changed {
             return None;
         }
 
-        let update = new_list.update_from(&self.prev_list);
+        let mut update =
+            new_list.update_from_with_flags(&self.prev_list, false, self.ignore_lifetime_changes);
+        update.diff.dns_changed = dns_changed;
+        update.dns = dns.clone();
         self.prev_list = new_list;
+        self.prev_dns = dns;
         Some(update)
     }
 }
 
+/// Configuration for `list_interfaces_with_options`.
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    include_down: bool,
+}
+
+impl ListOptions {
+    /// Include interfaces that are administratively down.
+    ///
+    /// By default only interfaces that are up are listed. On Windows, an adapter is considered
+    /// down when its operational status is down.
+    pub fn include_down(mut self, include_down: bool) -> Self {
+        self.include_down = include_down;
+        self
+    }
+}
+
+/// Configuration for a watcher.
+///
+/// The `watch_interfaces_*` functions that do not take options use `WatchOptions::default()`.
+#[derive(Debug, Clone, Default)]
+pub struct WatchOptions {
+    receive_buffer_size: Option<usize>,
+    include_down: bool,
+    ignore_lifetime_changes: bool,
+    dns: Option<DnsOptions>,
+}
+
+impl WatchOptions {
+    /// Include interfaces that are administratively down in each snapshot.
+    ///
+    /// With this enabled, bringing an interface down or up is reported as a modification with
+    /// `admin_state_changed` set, rather than as a removal or addition. See
+    /// `ListOptions::include_down`.
+    pub fn include_down(mut self, include_down: bool) -> Self {
+        self.include_down = include_down;
+        self
+    }
+
+    /// Do not deliver updates when the only change is to address lifetimes.
+    ///
+    /// The kernel reports an address again whenever its lifetimes are refreshed, e.g. by each
+    /// router advertisement, which otherwise produces an update with the address listed in
+    /// `addrs_modified`. With this enabled such refreshes are suppressed. The snapshot in the
+    /// next update that is delivered still carries the latest lifetimes.
+    pub fn ignore_lifetime_changes(mut self, ignore: bool) -> Self {
+        self.ignore_lifetime_changes = ignore;
+        self
+    }
+
+    /// Set the receive buffer size, in bytes, of the socket that delivers change notifications.
+    ///
+    /// A larger buffer makes it less likely that the kernel drops notifications during a burst of
+    /// changes. On Linux the limit in `net.core.rmem_max` is bypassed if the process has
+    /// `CAP_NET_ADMIN`. This applies to Linux and other Unix platforms that use a routing socket,
+    /// and is ignored on Windows and Android.
+    pub fn receive_buffer_size(mut self, bytes: usize) -> Self {
+        self.receive_buffer_size = Some(bytes);
+        self
+    }
+
+    /// Also watch the DNS resolver configuration, and carry it in `Update::dns`.
+    ///
+    /// The configuration files are watched with inotify. A change to them delivers an update
+    /// with `dns_changed` set, even if no interface changed. This applies to interface watches
+    /// on Linux, and is ignored on other platforms.
+    pub fn watch_dns(mut self, options: DnsOptions) -> Self {
+        self.dns = Some(options);
+        self
+    }
+}
+
+/// Counters describing how a watcher has kept up with platform notifications.
+///
+/// If the platform reports that notifications were lost, the watcher discards what it knows and
+/// lists every interface again, so later updates are still correct. These counters make that
+/// visible. They are always zero on Windows and Android.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WatchStats {
+    /// How many times the kernel dropped notifications because the socket buffer overflowed.
+    pub overruns: u64,
+    /// How many times the watcher listed all interfaces again after it started.
+    pub resyncs: u64,
+}
+
+/// Classify a Linux interface by its rtnetlink link kind, or failing that its ARPHRD type.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn interface_kind(link_kind: Option<&str>, arphrd: u16) -> InterfaceKind {
+    const ARPHRD_ETHER: u16 = 1;
+    const ARPHRD_LOOPBACK: u16 = 772;
+    const ARPHRD_NONE: u16 = 0xfffe;
+
+    match link_kind {
+        Some("bridge") => InterfaceKind::Bridge,
+        Some("veth") => InterfaceKind::Veth,
+        Some("vlan") => InterfaceKind::Vlan,
+        Some("tun") => InterfaceKind::Tun,
+        Some("wireguard") => InterfaceKind::Wireguard,
+        Some("bond") => InterfaceKind::Bond,
+        Some(other) => InterfaceKind::Other(other.to_string()),
+        None => match arphrd {
+            ARPHRD_ETHER => InterfaceKind::Ethernet,
+            ARPHRD_LOOPBACK => InterfaceKind::Loopback,
+            // Raw IP devices without a link kind are almost always tun devices, e.g. an Android
+            // VPN
+            ARPHRD_NONE => InterfaceKind::Tun,
+            other => InterfaceKind::Arphrd(other),
+        },
+    }
+}
+
 /// A handle to keep alive as long as you wish to receive callbacks.
 ///
 /// If the callback is executing at the time the handle is dropped, drop will block until
@@ -407,6 +998,65 @@ pub struct BlockingWatch {
     _inner: watch::BlockingWatch,
 }
 
+/// A handle that yields `RouteUpdate`s asynchronously when the routing tables change.
+pub struct AsyncRouteWatch {
+    _inner: routes::AsyncRouteWatch,
+}
+
+/// A handle that yields `RouteUpdate`s synchronously when the routing tables change.
+pub struct BlockingRouteWatch {
+    _inner: routes::BlockingRouteWatch,
+}
+
+/// A handle that yields `RuleUpdate`s asynchronously when the policy routing rules change.
+pub struct AsyncRuleWatch {
+    _inner: routes::AsyncRuleWatch,
+}
+
+/// A handle that yields `RuleUpdate`s synchronously when the policy routing rules change.
+pub struct BlockingRuleWatch {
+    _inner: routes::BlockingRuleWatch,
+}
+
+/// A handle that yields `PrimaryPathUpdate`s asynchronously when the primary path changes.
+pub struct AsyncPrimaryPathWatch {
+    _inner: routes::AsyncPrimaryPathWatch,
+}
+
+/// A handle that yields `PrimaryPathUpdate`s synchronously when the primary path changes.
+pub struct BlockingPrimaryPathWatch {
+    _inner: routes::BlockingPrimaryPathWatch,
+}
+
+/// A handle that yields `NeighbourUpdate`s asynchronously when the neighbour table changes.
+pub struct AsyncNeighbourWatch {
+    _inner: neighbours::AsyncNeighbourWatch,
+}
+
+/// A handle that yields `NeighbourUpdate`s synchronously when the neighbour table changes.
+pub struct BlockingNeighbourWatch {
+    _inner: neighbours::BlockingNeighbourWatch,
+}
+
+/// A handle that yields `RaDnsUpdate`s asynchronously when DNS configuration learned from router
+/// advertisements changes.
+pub struct AsyncRaDnsWatch {
+    _inner: ra_watch::AsyncRaDnsWatch,
+}
+
+/// A handle that yields `RaDnsUpdate`s synchronously when DNS configuration learned from router
+/// advertisements changes.
+pub struct BlockingRaDnsWatch {
+    _inner: ra_watch::BlockingRaDnsWatch,
+}
+
+impl WatchHandle {
+    /// Counters describing how this watcher has kept up with platform notifications.
+    pub fn stats(&self) -> WatchStats {
+        self._inner.stats()
+    }
+}
+
 impl AsyncWatch {
     /// Wait for the next interface snapshot that differs from the last snapshot yielded.
     ///
@@ -419,6 +1069,11 @@ impl AsyncWatch {
     pub async fn changed(&mut self) -> Update {
         self._inner.changed().await
     }
+
+    /// Counters describing how this watcher has kept up with platform notifications.
+    pub fn stats(&self) -> WatchStats {
+        self._inner.stats()
+    }
 }
 
 impl BlockingWatch {
@@ -433,6 +1088,11 @@ impl BlockingWatch {
     pub fn changed(&mut self) -> Update {
         self._inner.changed()
     }
+
+    /// Counters describing how this watcher has kept up with platform notifications.
+    pub fn stats(&self) -> WatchStats {
+        self._inner.stats()
+    }
 }
 
 /// Retrieve information about all enabled network interfaces and their IP addresses.
@@ -440,7 +1100,482 @@ impl BlockingWatch {
 /// This is a once-off operation. If you want to detect changes over time, see
 /// `watch_interfaces_with_callback`, `watch_interfaces_blocking`, or `watch_interfaces_async`.
 pub fn list_interfaces() -> Result<HashMap<IfIndex, Interface>, Error> {
-    list::list_interfaces().map(|list| list.0)
+    list_interfaces_with_options(&ListOptions::default())
+}
+
+/// Like `list_interfaces`, using the given options.
+pub fn list_interfaces_with_options(
+    options: &ListOptions,
+) -> Result<HashMap<IfIndex, Interface>, Error> {
+    list::list_interfaces().map(|list| list.visible(options.include_down).0)
+}
+
+/// Find the index of the interface with the given name or alternative name.
+///
+/// Interfaces that are down are included in the search. Returns `None` if no interface has the
+/// name.
+pub fn interface_index_by_name(name: &str) -> Result<Option<IfIndex>, Error> {
+    let list = list::list_interfaces()?;
+    Ok(list
+        .0
+        .values()
+        .find(|interface| interface.has_name(name))
+        .map(|interface| interface.index))
+}
+
+/// Retrieve the routes in every routing table, for both IPv4 and IPv6.
+///
+/// Routes are sorted by destination. This includes the kernel's local table, so filter on
+/// `Route::table` to see only the main table.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn list_routes() -> Result<Vec<Route>, Error> {
+    routes::list_routes()
+}
+
+/// Retrieve the policy routing rules for both IPv4 and IPv6, as `ip rule` shows them.
+///
+/// Rules are sorted by priority, which is the order the kernel evaluates them in. They decide
+/// which routing table is used for traffic, so on hosts that route by source address the main
+/// table alone does not tell the whole story.
+///
+/// Available on Linux. Other platforms, and Linux kernels built without policy routing, return
+/// `Error::Unsupported`.
+pub fn list_rules() -> Result<Vec<Rule>, Error> {
+    routes::list_rules()
+}
+
+/// Retrieve the kernel's neighbour table: the IPv4 (ARP) and IPv6 (neighbour discovery) entries
+/// for hosts on attached links.
+///
+/// Neighbours are sorted by interface index and then IP address. Entries are only present for
+/// hosts this one has recently communicated with or heard from, so this discovers peers without
+/// sending any packets.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn list_neighbours() -> Result<Vec<Neighbour>, Error> {
+    neighbours::list_neighbours()
+}
+
+/// Ask the kernel which interface, gateway and source address it would use to send traffic to
+/// `destination`, as `ip route get` does.
+///
+/// Unlike `primary_paths`, this applies every routing rule and route, including the most specific
+/// one for the destination. Returns `None` if the destination is unreachable.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn route_to(destination: IpAddr) -> Result<Option<RouteLookup>, Error> {
+    routes::route_to(destination)
+}
+
+/// Find the default route interface, gateway and source address that traffic to the internet
+/// would use right now, for each address family.
+///
+/// Policy routing rules are followed in priority order and the default route with the lowest
+/// metric is chosen within each table, as the kernel does. Rules that only match some traffic,
+/// such as by firewall mark or source address, are skipped. Default routes through interfaces
+/// that are not running are ignored.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn primary_paths() -> Result<PrimaryPaths, Error> {
+    routes::primary_paths()
+}
+
+/// Read the system's DNS resolver configuration from `/etc/resolv.conf`.
+///
+/// If that file only names the local systemd-resolved stub, the servers systemd-resolved forwards
+/// to are read from its uplink file instead, and `DnsConfig::uses_systemd_resolved` is set. To be
+/// told when the configuration changes, see `WatchOptions::watch_dns`.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn dns_config() -> Result<DnsConfig, Error> {
+    dns_config_with_options(&DnsOptions::default())
+}
+
+/// Like `dns_config`, reading the files given in the options.
+pub fn dns_config_with_options(options: &DnsOptions) -> Result<DnsConfig, Error> {
+    resolv::dns_config(options)
+}
+
+/// Read the traffic counters of every interface, keyed by interface index.
+///
+/// Counters are kept separate from `Interface` so that they never cause a watch to deliver an
+/// `Update`. Sample them as often as needed and compare two samples with
+/// `InterfaceStats::rates_since`.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn interface_stats() -> Result<HashMap<IfIndex, InterfaceStats>, Error> {
+    list::interface_stats()
+}
+
+/// Read the speed, duplex and driver of every interface, keyed by interface index.
+///
+/// Interfaces whose details are unknown, such as virtual devices, are included with every field
+/// set to `None`. Like traffic counters, these details are not part of `Interface` and do not
+/// cause watch updates.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn link_details() -> Result<HashMap<IfIndex, LinkDetails>, Error> {
+    list::link_details()
+}
+
+impl AsyncRouteWatch {
+    /// Wait for the next route snapshot that differs from the last snapshot yielded.
+    ///
+    /// The first call returns the current routes immediately. Subsequent calls wait until there
+    /// is a change. Like `AsyncWatch::changed`, this method is infallible.
+    pub async fn changed(&mut self) -> RouteUpdate {
+        self._inner.changed().await
+    }
+
+    /// Counters describing how this watcher has kept up with platform notifications.
+    pub fn stats(&self) -> WatchStats {
+        self._inner.stats()
+    }
+}
+
+impl BlockingRouteWatch {
+    /// Wait for the next route snapshot that differs from the last snapshot yielded.
+    ///
+    /// The first call returns the current routes immediately. Subsequent calls wait until there
+    /// is a change. Like `BlockingWatch::changed`, this method is infallible.
+    pub fn changed(&mut self) -> RouteUpdate {
+        self._inner.changed()
+    }
+
+    /// Counters describing how this watcher has kept up with platform notifications.
+    pub fn stats(&self) -> WatchStats {
+        self._inner.stats()
+    }
+}
+
+/// Retrieve the current routes and watch for changes, which will be delivered via callback.
+///
+/// This behaves like `watch_interfaces_with_callback`: the callback is invoked once with the
+/// initial routes before this function returns, then again on a background thread whenever the
+/// routes change. Only `WatchOptions::receive_buffer_size` applies to route watches.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn watch_routes_with_callback<F: FnMut(RouteUpdate) + Send + 'static>(
+    callback: F,
+    options: &WatchOptions,
+) -> Result<WatchHandle, Error> {
+    routes::watch_routes_with_callback(callback, options)
+        .map(|handle| WatchHandle { _inner: handle })
+}
+
+/// Retrieve the current routes and watch for changes synchronously.
+///
+/// The first call to `changed()` returns the current routes immediately. Only
+/// `WatchOptions::receive_buffer_size` applies to route watches.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn watch_routes_blocking(options: &WatchOptions) -> Result<BlockingRouteWatch, Error> {
+    routes::watch_routes_blocking(options).map(|handle| BlockingRouteWatch { _inner: handle })
+}
+
+/// Retrieve the current routes and watch for changes asynchronously using the given runtime
+/// adapter.
+///
+/// The first call to `changed()` returns the current routes immediately. Only
+/// `WatchOptions::receive_buffer_size` applies to route watches.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn watch_routes_async<A: async_adapter::AsyncFdAdapter>(
+    options: &WatchOptions,
+) -> Result<AsyncRouteWatch, Error> {
+    routes::watch_routes_async::<A>(options).map(|handle| AsyncRouteWatch { _inner: handle })
+}
+
+impl AsyncRuleWatch {
+    /// Wait for the next rule snapshot that differs from the last snapshot yielded.
+    ///
+    /// The first call returns the current rules immediately. Subsequent calls wait until there
+    /// is a change. Like `AsyncWatch::changed`, this method is infallible.
+    pub async fn changed(&mut self) -> RuleUpdate {
+        self._inner.changed().await
+    }
+
+    /// Counters describing how this watcher has kept up with platform notifications.
+    pub fn stats(&self) -> WatchStats {
+        self._inner.stats()
+    }
+}
+
+impl BlockingRuleWatch {
+    /// Wait for the next rule snapshot that differs from the last snapshot yielded.
+    ///
+    /// The first call returns the current rules immediately. Subsequent calls wait until there
+    /// is a change. Like `BlockingWatch::changed`, this method is infallible.
+    pub fn changed(&mut self) -> RuleUpdate {
+        self._inner.changed()
+    }
+
+    /// Counters describing how this watcher has kept up with platform notifications.
+    pub fn stats(&self) -> WatchStats {
+        self._inner.stats()
+    }
+}
+
+/// Retrieve the current policy routing rules and watch for changes, which will be delivered via
+/// callback.
+///
+/// This behaves like `watch_routes_with_callback`. Only `WatchOptions::receive_buffer_size`
+/// applies to rule watches.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn watch_rules_with_callback<F: FnMut(RuleUpdate) + Send + 'static>(
+    callback: F,
+    options: &WatchOptions,
+) -> Result<WatchHandle, Error> {
+    routes::watch_rules_with_callback(callback, options)
+        .map(|handle| WatchHandle { _inner: handle })
+}
+
+/// Retrieve the current policy routing rules and watch for changes synchronously.
+///
+/// The first call to `changed()` returns the current rules immediately. Only
+/// `WatchOptions::receive_buffer_size` applies to rule watches.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn watch_rules_blocking(options: &WatchOptions) -> Result<BlockingRuleWatch, Error> {
+    routes::watch_rules_blocking(options).map(|handle| BlockingRuleWatch { _inner: handle })
+}
+
+/// Retrieve the current policy routing rules and watch for changes asynchronously using the
+/// given runtime adapter.
+///
+/// The first call to `changed()` returns the current rules immediately. Only
+/// `WatchOptions::receive_buffer_size` applies to rule watches.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn watch_rules_async<A: async_adapter::AsyncFdAdapter>(
+    options: &WatchOptions,
+) -> Result<AsyncRuleWatch, Error> {
+    routes::watch_rules_async::<A>(options).map(|handle| AsyncRuleWatch { _inner: handle })
+}
+
+impl AsyncPrimaryPathWatch {
+    /// Wait for the next change to the primary path of either address family.
+    ///
+    /// The first call returns the current primary paths immediately. Like `AsyncWatch::changed`,
+    /// this method is infallible.
+    pub async fn changed(&mut self) -> PrimaryPathUpdate {
+        self._inner.changed().await
+    }
+
+    /// Counters describing how this watcher has kept up with platform notifications.
+    pub fn stats(&self) -> WatchStats {
+        self._inner.stats()
+    }
+}
+
+impl BlockingPrimaryPathWatch {
+    /// Wait for the next change to the primary path of either address family.
+    ///
+    /// The first call returns the current primary paths immediately. Like
+    /// `BlockingWatch::changed`, this method is infallible.
+    pub fn changed(&mut self) -> PrimaryPathUpdate {
+        self._inner.changed()
+    }
+
+    /// Counters describing how this watcher has kept up with platform notifications.
+    pub fn stats(&self) -> WatchStats {
+        self._inner.stats()
+    }
+}
+
+/// Retrieve the current primary paths and watch for changes, which will be delivered via
+/// callback.
+///
+/// Unlike a route watch, the callback is only invoked again when the result of `primary_paths`
+/// changes, not for every routing change. Only `WatchOptions::receive_buffer_size` applies.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn watch_primary_paths_with_callback<F: FnMut(PrimaryPathUpdate) + Send + 'static>(
+    callback: F,
+    options: &WatchOptions,
+) -> Result<WatchHandle, Error> {
+    routes::watch_primary_paths_with_callback(callback, options)
+        .map(|handle| WatchHandle { _inner: handle })
+}
+
+/// Retrieve the current primary paths and watch for changes synchronously.
+///
+/// The first call to `changed()` returns the current primary paths immediately. Only
+/// `WatchOptions::receive_buffer_size` applies.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn watch_primary_paths_blocking(
+    options: &WatchOptions,
+) -> Result<BlockingPrimaryPathWatch, Error> {
+    routes::watch_primary_paths_blocking(options)
+        .map(|handle| BlockingPrimaryPathWatch { _inner: handle })
+}
+
+/// Retrieve the current primary paths and watch for changes asynchronously using the given
+/// runtime adapter.
+///
+/// The first call to `changed()` returns the current primary paths immediately. Only
+/// `WatchOptions::receive_buffer_size` applies.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn watch_primary_paths_async<A: async_adapter::AsyncFdAdapter>(
+    options: &WatchOptions,
+) -> Result<AsyncPrimaryPathWatch, Error> {
+    routes::watch_primary_paths_async::<A>(options)
+        .map(|handle| AsyncPrimaryPathWatch { _inner: handle })
+}
+
+impl AsyncNeighbourWatch {
+    /// Wait for the next neighbour table snapshot that differs from the last snapshot yielded.
+    ///
+    /// The first call returns the current neighbour table immediately. Like
+    /// `AsyncWatch::changed`, this method is infallible.
+    pub async fn changed(&mut self) -> NeighbourUpdate {
+        self._inner.changed().await
+    }
+
+    /// Counters describing how this watcher has kept up with platform notifications.
+    pub fn stats(&self) -> WatchStats {
+        self._inner.stats()
+    }
+}
+
+impl BlockingNeighbourWatch {
+    /// Wait for the next neighbour table snapshot that differs from the last snapshot yielded.
+    ///
+    /// The first call returns the current neighbour table immediately. Like
+    /// `BlockingWatch::changed`, this method is infallible.
+    pub fn changed(&mut self) -> NeighbourUpdate {
+        self._inner.changed()
+    }
+
+    /// Counters describing how this watcher has kept up with platform notifications.
+    pub fn stats(&self) -> WatchStats {
+        self._inner.stats()
+    }
+}
+
+/// Retrieve the current neighbour table and watch for changes, which will be delivered via
+/// callback.
+///
+/// Neighbours move between reachability states regularly while they are in use, so expect
+/// frequent updates with `NeighbourDiff::modified` entries. A changed link-layer address, such as
+/// a gateway being replaced, shows up as a modification whose `before.hw_addr` and
+/// `after.hw_addr` differ. Only `WatchOptions::receive_buffer_size` applies.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn watch_neighbours_with_callback<F: FnMut(NeighbourUpdate) + Send + 'static>(
+    callback: F,
+    options: &WatchOptions,
+) -> Result<WatchHandle, Error> {
+    neighbours::watch_neighbours_with_callback(callback, options)
+        .map(|handle| WatchHandle { _inner: handle })
+}
+
+/// Retrieve the current neighbour table and watch for changes synchronously.
+///
+/// The first call to `changed()` returns the current neighbour table immediately. Only
+/// `WatchOptions::receive_buffer_size` applies.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn watch_neighbours_blocking(options: &WatchOptions) -> Result<BlockingNeighbourWatch, Error> {
+    neighbours::watch_neighbours_blocking(options)
+        .map(|handle| BlockingNeighbourWatch { _inner: handle })
+}
+
+/// Retrieve the current neighbour table and watch for changes asynchronously using the given
+/// runtime adapter.
+///
+/// The first call to `changed()` returns the current neighbour table immediately. Only
+/// `WatchOptions::receive_buffer_size` applies.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn watch_neighbours_async<A: async_adapter::AsyncFdAdapter>(
+    options: &WatchOptions,
+) -> Result<AsyncNeighbourWatch, Error> {
+    neighbours::watch_neighbours_async::<A>(options)
+        .map(|handle| AsyncNeighbourWatch { _inner: handle })
+}
+
+impl AsyncRaDnsWatch {
+    /// Wait for the next change to the advertised DNS configuration.
+    ///
+    /// The first call returns an empty update immediately. Like `AsyncWatch::changed`, this
+    /// method is infallible.
+    pub async fn changed(&mut self) -> RaDnsUpdate {
+        self._inner.changed().await
+    }
+
+    /// Counters describing how this watcher has kept up with platform notifications. Lost
+    /// notifications are not counted, because nothing can be relisted to recover them.
+    pub fn stats(&self) -> WatchStats {
+        self._inner.stats()
+    }
+}
+
+impl BlockingRaDnsWatch {
+    /// Wait for the next change to the advertised DNS configuration.
+    ///
+    /// The first call returns an empty update immediately. Like `BlockingWatch::changed`, this
+    /// method is infallible.
+    pub fn changed(&mut self) -> RaDnsUpdate {
+        self._inner.changed()
+    }
+
+    /// Counters describing how this watcher has kept up with platform notifications. Lost
+    /// notifications are not counted, because nothing can be relisted to recover them.
+    pub fn stats(&self) -> WatchStats {
+        self._inner.stats()
+    }
+}
+
+/// Watch the DNS servers (RDNSS) and search domains (DNSSL) that IPv6 routers advertise, which
+/// will be delivered via callback.
+///
+/// On networks without DHCPv6 these options are often the only source of DNS configuration. The
+/// kernel does not keep them, so only advertisements received after the watch starts are known:
+/// the initial update is empty, and the first entries usually arrive with the next periodic
+/// advertisement. Entries are removed when a router withdraws them, when their lifetime runs out
+/// or when their interface is removed, and each of these produces an update. The interface must
+/// accept router advertisements, as set by the `accept_ra` sysctl. Only
+/// `WatchOptions::receive_buffer_size` applies.
+///
+/// Prefix information is not reported. The kernel acts on it instead of passing it on: the
+/// addresses it configures appear in `watch_interfaces_with_callback`, and the on-link and
+/// default routes it adds appear in `watch_routes_with_callback`.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn watch_ra_dns_with_callback<F: FnMut(RaDnsUpdate) + Send + 'static>(
+    callback: F,
+    options: &WatchOptions,
+) -> Result<WatchHandle, Error> {
+    ra_watch::watch_ra_dns_with_callback(callback, options)
+        .map(|handle| WatchHandle { _inner: handle })
+}
+
+/// Watch the DNS servers and search domains that IPv6 routers advertise synchronously.
+///
+/// The first call to `changed()` returns an empty update immediately, as described for
+/// `watch_ra_dns_with_callback`. Only `WatchOptions::receive_buffer_size` applies.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn watch_ra_dns_blocking(options: &WatchOptions) -> Result<BlockingRaDnsWatch, Error> {
+    ra_watch::watch_ra_dns_blocking(options).map(|handle| BlockingRaDnsWatch { _inner: handle })
+}
+
+/// Watch the DNS servers and search domains that IPv6 routers advertise asynchronously using
+/// the given runtime adapter.
+///
+/// The first call to `changed()` returns an empty update immediately, as described for
+/// `watch_ra_dns_with_callback`. Only `WatchOptions::receive_buffer_size` applies.
+///
+/// Available on Linux. Other platforms return `Error::Unsupported`.
+pub fn watch_ra_dns_async<A: async_adapter::AsyncFdAdapter>(
+    options: &WatchOptions,
+) -> Result<AsyncRaDnsWatch, Error> {
+    ra_watch::watch_ra_dns_async::<A>(options).map(|handle| AsyncRaDnsWatch { _inner: handle })
 }
 
 /// Retrieve interface information and watch for changes, which will be delivered via callback.
@@ -470,21 +1605,44 @@ pub fn list_interfaces() -> Result<HashMap<IfIndex, Interface>, Error> {
 pub fn watch_interfaces_with_callback<F: FnMut(Update) + Send + 'static>(
     callback: F,
 ) -> Result<WatchHandle, Error> {
-    watch::watch_interfaces_with_callback(callback).map(|handle| WatchHandle { _inner: handle })
+    watch_interfaces_with_callback_and_options(callback, &WatchOptions::default())
+}
+
+/// Like `watch_interfaces_with_callback`, using the given options.
+pub fn watch_interfaces_with_callback_and_options<F: FnMut(Update) + Send + 'static>(
+    callback: F,
+    options: &WatchOptions,
+) -> Result<WatchHandle, Error> {
+    watch::watch_interfaces_with_callback(callback, options)
+        .map(|handle| WatchHandle { _inner: handle })
 }
 
 /// Retrieve interface information and watch for changes synchronously.
 ///
 /// The first call to `changed()` returns the current interface snapshot immediately.
 pub fn watch_interfaces_blocking() -> Result<BlockingWatch, Error> {
-    watch::watch_interfaces_blocking().map(|handle| BlockingWatch { _inner: handle })
+    watch_interfaces_blocking_with_options(&WatchOptions::default())
+}
+
+/// Like `watch_interfaces_blocking`, using the given options.
+pub fn watch_interfaces_blocking_with_options(
+    options: &WatchOptions,
+) -> Result<BlockingWatch, Error> {
+    watch::watch_interfaces_blocking(options).map(|handle| BlockingWatch { _inner: handle })
 }
 
 /// Retrieve interface information and watch for changes asynchronously using the given runtime adapter.
 ///
 /// The first call to `changed()` returns the current interface snapshot immediately.
 pub fn watch_interfaces_async<A: async_adapter::AsyncFdAdapter>() -> Result<AsyncWatch, Error> {
-    watch::watch_interfaces_async::<A>().map(|handle| AsyncWatch { _inner: handle })
+    watch_interfaces_async_with_options::<A>(&WatchOptions::default())
+}
+
+/// Like `watch_interfaces_async`, using the given options.
+pub fn watch_interfaces_async_with_options<A: async_adapter::AsyncFdAdapter>(
+    options: &WatchOptions,
+) -> Result<AsyncWatch, Error> {
+    watch::watch_interfaces_async::<A>(options).map(|handle| AsyncWatch { _inner: handle })
 }
 
 #[cfg(test)]
@@ -507,8 +1665,9 @@ mod tests {
         let mut interface = Interface {
             index,
             name: name.into(),
-            hw_addr: hw_addr.into(),
+            hw_addr: hw_addr.parse().ok(),
             ips: ips.into_iter().collect(),
+            ..Default::default()
         };
         interface.normalise();
         interface
@@ -581,9 +1740,18 @@ mod tests {
                 after.index,
                 InterfaceDiff {
                     name_changed: true,
+                    alt_names_changed: false,
+                    alias_changed: false,
                     hw_addr_changed: true,
+                    flags_changed: false,
+                    admin_state_changed: false,
+                    oper_state_changed: false,
+                    mtu_changed: false,
+                    master_changed: false,
+                    link_changed: false,
                     addrs_added: vec![ip(12)],
                     addrs_removed: vec![ip(11)],
+                    addrs_modified: Vec::new(),
                 }
             )])
         );
@@ -610,9 +1778,18 @@ mod tests {
                 1,
                 InterfaceDiff {
                     name_changed: true,
+                    alt_names_changed: false,
+                    alias_changed: false,
                     hw_addr_changed: false,
+                    flags_changed: false,
+                    admin_state_changed: false,
+                    oper_state_changed: false,
+                    mtu_changed: false,
+                    master_changed: false,
+                    link_changed: false,
                     addrs_added: Vec::new(),
                     addrs_removed: Vec::new(),
+                    addrs_modified: Vec::new(),
                 }
             )])
         );
@@ -620,6 +1797,237 @@ mod tests {
         assert_eq!(update.addrs_removed().next(), None);
     }
 
+    #[test]
+    fn carrier_loss_is_reported_as_an_oper_state_change() {
+        let mut before = interface(1, "eth0", "00:00:00:00:00:01", [ip(1)]);
+        before.flags.running = true;
+        before.flags.lower_up = true;
+        before.oper_state = Some(OperState::Up);
+        before.carrier = Some(true);
+        let mut after = before.clone();
+        after.flags.lower_up = false;
+        after.oper_state = Some(OperState::LowerLayerDown);
+        after.carrier = Some(false);
+
+        let update = list([after]).update_from(&list([before]));
+
+        assert_eq!(
+            update.diff.modified,
+            HashMap::from([(
+                1,
+                InterfaceDiff {
+                    flags_changed: true,
+                    oper_state_changed: true,
+                    ..Default::default()
+                }
+            )])
+        );
+    }
+
+    #[test]
+    fn bringing_an_interface_down_is_a_removal_unless_down_interfaces_are_included() {
+        let mut up = interface(1, "eth0", "00:00:00:00:00:01", [ip(1)]);
+        up.flags.up = true;
+        let mut down = up.clone();
+        down.flags.up = false;
+
+        let mut cursor = UpdateCursor::default();
+        cursor.advance(list([up.clone()])).unwrap();
+        let update = cursor.advance(list([down.clone()])).unwrap();
+        assert_eq!(update.diff.removed, HashMap::from([(1, up.clone())]));
+        assert!(update.interfaces.is_empty());
+
+        let mut cursor = UpdateCursor::new(&WatchOptions::default().include_down(true));
+        cursor.advance(list([up])).unwrap();
+        let update = cursor.advance(list([down])).unwrap();
+        assert!(update.diff.removed.is_empty());
+        assert_eq!(
+            update.diff.modified,
+            HashMap::from([(
+                1,
+                InterfaceDiff {
+                    flags_changed: true,
+                    admin_state_changed: true,
+                    ..Default::default()
+                }
+            )])
+        );
+    }
+
+    #[test]
+    fn mtu_only_change_produces_an_update() {
+        let mut before = interface(1, "eth0", "00:00:00:00:00:01", [ip(1)]);
+        before.mtu = Some(1500);
+        let mut after = before.clone();
+        after.mtu = Some(1400);
+
+        let update = list([after]).update_from(&list([before]));
+
+        assert_eq!(
+            update.diff.modified,
+            HashMap::from([(
+                1,
+                InterfaceDiff {
+                    mtu_changed: true,
+                    ..Default::default()
+                }
+            )])
+        );
+    }
+
+    #[test]
+    fn alt_name_and_alias_changes_are_reported() {
+        let before = interface(1, "eth0", "00:00:00:00:00:01", [ip(1)]);
+        let mut after = before.clone();
+        after.alt_names = vec!["enp0s31f6".to_string()];
+        after.alias = Some("uplink".to_string());
+
+        let update = list([after.clone()]).update_from(&list([before]));
+
+        assert_eq!(
+            update.diff.modified,
+            HashMap::from([(
+                1,
+                InterfaceDiff {
+                    alt_names_changed: true,
+                    alias_changed: true,
+                    ..Default::default()
+                }
+            )])
+        );
+        assert!(after.has_name("eth0"));
+        assert!(after.has_name("enp0s31f6"));
+        assert!(!after.has_name("uplink"));
+    }
+
+    #[test]
+    fn randomised_hw_addr_is_not_permanent() {
+        let mut interface = interface(1, "wlan0", "02:00:00:00:00:01", [ip(1)]);
+        assert!(!interface.has_non_permanent_hw_addr());
+
+        interface.perm_hw_addr = "02:00:00:00:00:01".parse().ok();
+        assert!(!interface.has_non_permanent_hw_addr());
+
+        interface.hw_addr = "5a:11:22:33:44:55".parse().ok();
+        assert!(interface.has_non_permanent_hw_addr());
+    }
+
+    #[test]
+    fn joining_a_bridge_is_a_master_change() {
+        let before = interface(2, "veth0", "00:00:00:00:00:02", [ip(1)]);
+        let mut after = before.clone();
+        after.master = Some(5);
+
+        let update = list([after]).update_from(&list([before]));
+
+        assert_eq!(
+            update.diff.modified,
+            HashMap::from([(
+                2,
+                InterfaceDiff {
+                    master_changed: true,
+                    ..Default::default()
+                }
+            )])
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn link_kind_takes_precedence_over_hardware_type() {
+        assert_eq!(interface_kind(Some("veth"), 1), InterfaceKind::Veth);
+        assert_eq!(interface_kind(None, 1), InterfaceKind::Ethernet);
+        assert_eq!(interface_kind(None, 772), InterfaceKind::Loopback);
+        assert_eq!(
+            interface_kind(Some("macvlan"), 1),
+            InterfaceKind::Other("macvlan".to_string())
+        );
+        assert_eq!(interface_kind(None, 512), InterfaceKind::Arphrd(512));
+    }
+
+    #[test]
+    fn address_flag_transition_is_reported_as_a_modified_address() {
+        let mut before = interface(1, "eth0", "00:00:00:00:00:01", [ip(1), ip(2)]);
+        let tentative = AddrInfo {
+            flags: AddrFlags {
+                tentative: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        before.addr_info.insert(ip(1), tentative);
+        before.addr_info.insert(ip(2), AddrInfo::default());
+        let mut after = before.clone();
+        after.addr_info.insert(ip(1), AddrInfo::default());
+
+        let update = list([after]).update_from(&list([before]));
+
+        assert_eq!(
+            update.diff.modified,
+            HashMap::from([(
+                1,
+                InterfaceDiff {
+                    addrs_modified: vec![ip(1)],
+                    ..Default::default()
+                }
+            )])
+        );
+        assert_eq!(
+            owned_addresses(update.addrs_modified()),
+            HashSet::from([(1, ip(1))])
+        );
+        assert_eq!(update.addrs_added().next(), None);
+        assert_eq!(update.addrs_removed().next(), None);
+    }
+
+    #[test]
+    fn lifetime_refresh_can_be_ignored() {
+        let reported_at = Instant::now();
+        let lifetimes = |valid: u64| AddrLifetimes {
+            valid: Some(Duration::from_secs(valid)),
+            preferred: Some(Duration::from_secs(valid / 2)),
+            reported_at,
+        };
+        let mut before = interface(1, "eth0", "00:00:00:00:00:01", [ip(1)]);
+        before.flags.up = true;
+        before.addr_info.insert(
+            ip(1),
+            AddrInfo {
+                lifetimes: Some(lifetimes(3600)),
+                ..Default::default()
+            },
+        );
+        let mut refreshed = before.clone();
+        refreshed.addr_info.get_mut(&ip(1)).unwrap().lifetimes = Some(lifetimes(7200));
+        let mut renamed = refreshed.clone();
+        renamed.name = "wan0".into();
+
+        let mut cursor = UpdateCursor::default();
+        cursor.advance(list([before.clone()])).unwrap();
+        let update = cursor.advance(list([refreshed.clone()])).unwrap();
+        assert_eq!(update.diff.modified[&1].addrs_modified, vec![ip(1)]);
+
+        let options = WatchOptions::default().ignore_lifetime_changes(true);
+        let mut cursor = UpdateCursor::new(&options);
+        cursor.advance(list([before])).unwrap();
+        assert_eq!(cursor.advance(list([refreshed.clone()])), None);
+        let update = cursor.advance(list([renamed])).unwrap();
+        assert_eq!(
+            update.diff.modified,
+            HashMap::from([(
+                1,
+                InterfaceDiff {
+                    name_changed: true,
+                    ..Default::default()
+                }
+            )])
+        );
+        assert_eq!(
+            update.interfaces[&1].addr_info[&ip(1)].lifetimes,
+            Some(lifetimes(7200))
+        );
+    }
+
     #[test]
     fn unchanged_update_has_an_empty_diff() {
         let current = list([interface(1, "unchanged", "00:00:00:00:00:01", [ip(1)])]);
@@ -688,9 +2096,18 @@ mod tests {
                 1,
                 InterfaceDiff {
                     name_changed: false,
+                    alt_names_changed: false,
+                    alias_changed: false,
                     hw_addr_changed: false,
+                    flags_changed: false,
+                    admin_state_changed: false,
+                    oper_state_changed: false,
+                    mtu_changed: false,
+                    master_changed: false,
+                    link_changed: false,
                     addrs_added: vec![ip(4)],
                     addrs_removed: vec![ip(2)],
+                    addrs_modified: Vec::new(),
                 }
             )])
         );
@@ -709,8 +2126,9 @@ mod tests {
         let mut iface = Interface {
             index: 1,
             name: "iface".into(),
-            hw_addr: "00:00:00:00:00:01".into(),
+            hw_addr: "00:00:00:00:00:01".parse().ok(),
             ips: vec![ip(3), ip(1), ip(3), ip(2), ip(1)],
+            ..Default::default()
         };
 
         iface.normalise();
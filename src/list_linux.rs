@@ -0,0 +1,519 @@
+use std::collections::{BTreeMap, HashMap};
+use std::os::fd::AsFd;
+use std::time::{Duration, Instant};
+
+use nix::sys::socket::{socket, AddressFamily, SockFlag, SockType};
+
+use crate::netlink::{
+    parse_addr, parse_link, AddrMessage, LinkMessage, LinkStats, Message, RouteSocket,
+    IFA_F_DADFAILED, IFA_F_DEPRECATED, IFA_F_OPTIMISTIC, IFA_F_PERMANENT, IFA_F_SECONDARY,
+    IFA_F_TEMPORARY, IFA_F_TENTATIVE, IFF_BROADCAST, IFF_LOOPBACK, IFF_LOWER_UP, IFF_MULTICAST,
+    IFF_POINTOPOINT, IFF_RUNNING, IFF_UP, INFINITY_LIFE_TIME, RTM_DELADDR, RTM_DELLINK,
+    RTM_NEWADDR, RTM_NEWLINK,
+};
+use crate::sysfs_linux;
+use crate::watch_fd::Drained;
+use crate::{
+    interface_kind, AddrFlags, AddrInfo, AddrLifetimes, AddrScope, Error, HardwareAddr, Interface,
+    InterfaceStats, IpRecord, LinkDetails, LinkFlags, List, OperState,
+};
+
+pub(crate) fn list_interfaces() -> Result<List, Error> {
+    let mut socket = RouteSocket::open()?;
+    let links = socket.dump_links()?;
+    let addrs = socket.dump_addrs()?;
+    build_list(links, addrs)
+}
+
+pub(crate) fn interface_stats() -> Result<HashMap<u32, InterfaceStats>, Error> {
+    let links = RouteSocket::open()?.dump_links()?;
+    let sampled_at = Instant::now();
+    Ok(links
+        .into_iter()
+        .filter_map(|link| {
+            let stats = link.stats?;
+            Some((link.index, interface_stats_from(stats, sampled_at)))
+        })
+        .collect())
+}
+
+pub(crate) fn link_details() -> Result<HashMap<u32, LinkDetails>, Error> {
+    let links = RouteSocket::open()?.dump_links()?;
+    // The ethtool ioctl needs a socket of any kind. Without one autoneg is simply unknown.
+    let ioctl_socket = socket(
+        AddressFamily::Inet,
+        SockType::Datagram,
+        SockFlag::SOCK_CLOEXEC,
+        None,
+    )
+    .ok();
+    Ok(links
+        .into_iter()
+        .filter_map(|link| {
+            let name = link.name?;
+            let socket = ioctl_socket.as_ref().map(|socket| socket.as_fd());
+            Some((link.index, sysfs_linux::link_details(socket, &name)))
+        })
+        .collect())
+}
+
+fn interface_stats_from(stats: LinkStats, sampled_at: Instant) -> InterfaceStats {
+    InterfaceStats {
+        rx_bytes: stats.rx_bytes,
+        tx_bytes: stats.tx_bytes,
+        rx_packets: stats.rx_packets,
+        tx_packets: stats.tx_packets,
+        rx_errors: stats.rx_errors,
+        tx_errors: stats.tx_errors,
+        rx_dropped: stats.rx_dropped,
+        tx_dropped: stats.tx_dropped,
+        sampled_at,
+    }
+}
+
+fn build_list(links: Vec<LinkMessage>, addrs: Vec<AddrMessage>) -> Result<List, Error> {
+    let reported_at = Instant::now();
+    let mut ifs = HashMap::new();
+    for link in links {
+        let interface = interface_from_link(link);
+        ifs.insert(interface.index, interface);
+    }
+    // Addresses on interfaces that appeared between the two dumps are not part of this
+    // snapshot. They are replayed by the notifications that follow.
+    for addr in addrs {
+        if let Some(interface) = ifs.get_mut(&addr.index) {
+            insert_addr(interface, &addr, reported_at);
+        }
+    }
+    for interface in ifs.values_mut() {
+        interface.normalise();
+    }
+    Ok(List(ifs))
+}
+
+/// Apply a link or address notification to a list previously built by `list_interfaces`.
+///
+/// Messages are applied in the order the kernel sent them, so replaying notifications that
+/// were already reflected in the initial dump converges on the same state. Links are tracked
+/// whether or not they are up, so a link that is not yet known is new and has no addresses.
+pub(crate) fn apply_message(list: &mut List, message: &Message<'_>) -> Drained {
+    match message.kind {
+        RTM_NEWLINK => {
+            let Some(link) = parse_link(message.payload) else {
+                return Drained::Applied;
+            };
+            let mut updated = interface_from_link(link);
+            if let Some(existing) = list.0.get_mut(&updated.index) {
+                updated.ips = std::mem::take(&mut existing.ips);
+                updated.addr_info = std::mem::take(&mut existing.addr_info);
+                *existing = updated;
+            } else {
+                list.0.insert(updated.index, updated);
+            }
+        }
+        RTM_DELLINK => {
+            if let Some(link) = parse_link(message.payload) {
+                list.0.remove(&link.index);
+            }
+        }
+        RTM_NEWADDR => {
+            let Some(addr) = parse_addr(message.payload) else {
+                return Drained::Applied;
+            };
+            if let Some(interface) = list.0.get_mut(&addr.index) {
+                insert_addr(interface, &addr, Instant::now());
+                interface.normalise();
+            }
+        }
+        RTM_DELADDR => {
+            let Some(addr) = parse_addr(message.payload) else {
+                return Drained::Applied;
+            };
+            if let Some(interface) = list.0.get_mut(&addr.index) {
+                remove_addr(interface, &addr);
+            }
+        }
+        _ => {}
+    }
+    Drained::Applied
+}
+
+/// Add an address to an interface, or replace the information for one it already has. The
+/// caller normalises the address list.
+///
+/// A point-to-point address is configured once per peer, so the peers of the local address are
+/// collected rather than replaced.
+fn insert_addr(interface: &mut Interface, addr: &AddrMessage, reported_at: Instant) {
+    let record = IpRecord {
+        ip: addr.ip,
+        prefix_len: addr.prefix_len,
+    };
+    let mut peers = interface
+        .addr_info
+        .get(&record)
+        .map(|info| info.peers.clone())
+        .unwrap_or_default();
+    peers.extend(addr.peer);
+    peers.sort();
+    peers.dedup();
+    let info = AddrInfo {
+        flags: addr_flags(addr),
+        lifetimes: addr
+            .lifetimes
+            .and_then(|(preferred, valid)| addr_lifetimes(preferred, valid, reported_at)),
+        broadcast: addr.broadcast,
+        peers,
+        scope: Some(addr_scope(addr.scope)),
+        label: addr.label.clone(),
+    };
+    interface.ips.push(record.clone());
+    interface.addr_info.insert(record, info);
+}
+
+/// Remove an address from an interface. The address stays while it is still configured for
+/// another peer.
+fn remove_addr(interface: &mut Interface, addr: &AddrMessage) {
+    let record = IpRecord {
+        ip: addr.ip,
+        prefix_len: addr.prefix_len,
+    };
+    if let (Some(peer), Some(info)) = (addr.peer, interface.addr_info.get_mut(&record)) {
+        info.peers.retain(|&other| other != peer);
+        if !info.peers.is_empty() {
+            return;
+        }
+    }
+    interface.ips.retain(|ip| *ip != record);
+    interface.addr_info.remove(&record);
+}
+
+fn addr_flags(addr: &AddrMessage) -> AddrFlags {
+    let flags = addr.flags;
+    let ipv4 = addr.ip.is_ipv4();
+    AddrFlags {
+        secondary: ipv4 && flags & IFA_F_SECONDARY != 0,
+        temporary: !ipv4 && flags & IFA_F_TEMPORARY != 0,
+        tentative: flags & IFA_F_TENTATIVE != 0,
+        optimistic: flags & IFA_F_OPTIMISTIC != 0,
+        dad_failed: flags & IFA_F_DADFAILED != 0,
+        deprecated: flags & IFA_F_DEPRECATED != 0,
+        permanent: flags & IFA_F_PERMANENT != 0,
+    }
+}
+
+// RT_SCOPE_* values from rtnetlink.h
+fn addr_scope(value: u8) -> AddrScope {
+    match value {
+        0 => AddrScope::Global,
+        200 => AddrScope::Site,
+        253 => AddrScope::Link,
+        254 => AddrScope::Host,
+        255 => AddrScope::Nowhere,
+        other => AddrScope::Other(other),
+    }
+}
+
+/// Convert IFA_CACHEINFO lifetimes, or `None` if the address never expires.
+fn addr_lifetimes(preferred: u32, valid: u32, reported_at: Instant) -> Option<AddrLifetimes> {
+    let lifetime =
+        |seconds: u32| (seconds != INFINITY_LIFE_TIME).then(|| Duration::from_secs(seconds.into()));
+    let lifetimes = AddrLifetimes {
+        valid: lifetime(valid),
+        preferred: lifetime(preferred),
+        reported_at,
+    };
+    (lifetimes.valid.is_some() || lifetimes.preferred.is_some()).then_some(lifetimes)
+}
+
+fn interface_from_link(link: LinkMessage) -> Interface {
+    Interface {
+        index: link.index,
+        name: link.name.unwrap_or_default(),
+        alt_names: link.alt_names,
+        alias: link.alias,
+        hw_addr: link
+            .hw_addr
+            .and_then(|raw_addr| HardwareAddr::from_bytes(&raw_addr)),
+        perm_hw_addr: link
+            .perm_hw_addr
+            .and_then(|raw_addr| HardwareAddr::from_bytes(&raw_addr)),
+        ips: vec![],
+        addr_info: BTreeMap::new(),
+        flags: link_flags(link.flags),
+        oper_state: link.oper_state.map(oper_state),
+        carrier: link.carrier,
+        mtu: link.mtu,
+        kind: Some(interface_kind(link.kind.as_deref(), link.arphrd)),
+        master: link.master,
+        link: link.link,
+        link_netnsid: link.link_netnsid,
+    }
+}
+
+fn link_flags(flags: u32) -> LinkFlags {
+    LinkFlags {
+        up: flags & IFF_UP != 0,
+        running: flags & IFF_RUNNING != 0,
+        lower_up: flags & IFF_LOWER_UP != 0,
+        loopback: flags & IFF_LOOPBACK != 0,
+        point_to_point: flags & IFF_POINTOPOINT != 0,
+        multicast: flags & IFF_MULTICAST != 0,
+        broadcast: flags & IFF_BROADCAST != 0,
+    }
+}
+
+// IF_OPER_* values from the kernel's RFC 2863 operstate
+fn oper_state(value: u8) -> OperState {
+    match value {
+        1 => OperState::NotPresent,
+        2 => OperState::Down,
+        3 => OperState::LowerLayerDown,
+        4 => OperState::Testing,
+        5 => OperState::Dormant,
+        6 => OperState::Up,
+        _ => OperState::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::netlink::messages;
+    use crate::netlink::tests::{addr_flags, addr_local, addr_payload, link_payload, message};
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn link(index: u32, flags: u32, name: &str) -> LinkMessage {
+        parse_link(&link_payload(
+            index,
+            flags,
+            name,
+            &[2, 0, 0, 0, 0, index as u8],
+        ))
+        .unwrap()
+    }
+
+    fn addr(index: u32, last_octet: u8) -> AddrMessage {
+        parse_addr(&addr_payload(
+            index,
+            IpAddr::V4(Ipv4Addr::new(192, 0, 2, last_octet)),
+            24,
+        ))
+        .unwrap()
+    }
+
+    fn record(last_octet: u8) -> IpRecord {
+        IpRecord {
+            ip: IpAddr::V4(Ipv4Addr::new(192, 0, 2, last_octet)),
+            prefix_len: 24,
+        }
+    }
+
+    fn apply(list: &mut List, kind: u16, payload: &[u8]) -> Drained {
+        let buf = message(kind, payload);
+        let message = messages(&buf).next().unwrap();
+        apply_message(list, &message)
+    }
+
+    fn addr_change(index: u32, last_octet: u8) -> Vec<u8> {
+        let record = record(last_octet);
+        addr_payload(index, record.ip, record.prefix_len)
+    }
+
+    #[test]
+    fn address_notifications_are_applied_in_place() {
+        let mut list = build_list(vec![link(1, IFF_UP, "eth0")], vec![addr(1, 1)]).unwrap();
+
+        assert_eq!(
+            apply(&mut list, RTM_NEWADDR, &addr_change(1, 2)),
+            Drained::Applied
+        );
+        assert_eq!(
+            apply(&mut list, RTM_NEWADDR, &addr_change(1, 2)),
+            Drained::Applied
+        );
+        assert_eq!(list.0[&1].ips, vec![record(1), record(2)]);
+
+        assert_eq!(
+            apply(&mut list, RTM_DELADDR, &addr_change(1, 1)),
+            Drained::Applied
+        );
+        assert_eq!(list.0[&1].ips, vec![record(2)]);
+
+        // Addresses for interfaces outside the snapshot are ignored.
+        assert_eq!(
+            apply(&mut list, RTM_NEWADDR, &addr_change(9, 3)),
+            Drained::Applied
+        );
+        assert_eq!(list.0.len(), 1);
+    }
+
+    #[test]
+    fn address_flag_changes_replace_the_address_info() {
+        let mut list = build_list(vec![link(1, IFF_UP, "eth0")], vec![]).unwrap();
+        let v6 = IpAddr::V6("2001:db8::1".parse().unwrap());
+        let record = IpRecord {
+            ip: v6,
+            prefix_len: 64,
+        };
+
+        let mut tentative = addr_payload(1, v6, 64);
+        tentative.extend(addr_flags(IFA_F_TENTATIVE | IFA_F_TEMPORARY));
+        apply(&mut list, RTM_NEWADDR, &tentative);
+        let flags = list.0[&1].addr_info[&record].flags;
+        assert!(flags.tentative);
+        assert!(flags.temporary);
+        assert!(!flags.secondary);
+
+        let mut preferred = addr_payload(1, v6, 64);
+        preferred.extend(addr_flags(IFA_F_TEMPORARY));
+        apply(&mut list, RTM_NEWADDR, &preferred);
+        assert_eq!(list.0[&1].ips, vec![record.clone()]);
+        assert!(!list.0[&1].addr_info[&record].flags.tentative);
+
+        apply(&mut list, RTM_DELADDR, &preferred);
+        assert!(list.0[&1].ips.is_empty());
+        assert!(list.0[&1].addr_info.is_empty());
+    }
+
+    #[test]
+    fn point_to_point_addresses_keep_every_peer() {
+        let mut list = build_list(vec![link(1, IFF_UP, "tun0")], vec![]).unwrap();
+        let local = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let record = IpRecord {
+            ip: local,
+            prefix_len: 32,
+        };
+        let with_peer = |peer: u8| {
+            let mut payload = addr_payload(1, IpAddr::V4(Ipv4Addr::new(10, 0, 0, peer)), 32);
+            payload.extend(addr_local(local));
+            payload
+        };
+
+        apply(&mut list, RTM_NEWADDR, &with_peer(3));
+        apply(&mut list, RTM_NEWADDR, &with_peer(2));
+        assert_eq!(list.0[&1].ips, vec![record.clone()]);
+        assert_eq!(
+            list.0[&1].addr_info[&record].peers,
+            vec![
+                IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)),
+                IpAddr::V4(Ipv4Addr::new(10, 0, 0, 3)),
+            ]
+        );
+
+        apply(&mut list, RTM_DELADDR, &with_peer(2));
+        assert_eq!(list.0[&1].ips, vec![record.clone()]);
+        assert_eq!(
+            list.0[&1].addr_info[&record].peers,
+            vec![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 3))]
+        );
+
+        apply(&mut list, RTM_DELADDR, &with_peer(3));
+        assert!(list.0[&1].ips.is_empty());
+        assert!(list.0[&1].addr_info.is_empty());
+    }
+
+    #[test]
+    fn infinite_lifetimes_are_not_reported() {
+        let now = Instant::now();
+
+        assert_eq!(
+            addr_lifetimes(INFINITY_LIFE_TIME, INFINITY_LIFE_TIME, now),
+            None
+        );
+        let lifetimes = addr_lifetimes(0, INFINITY_LIFE_TIME, now).unwrap();
+        assert_eq!(lifetimes.preferred, Some(Duration::ZERO));
+        assert_eq!(lifetimes.valid, None);
+        let lifetimes = addr_lifetimes(1800, 3600, now).unwrap();
+        assert_eq!(
+            lifetimes.valid_until(),
+            Some(now + Duration::from_secs(3600))
+        );
+        assert_eq!(
+            lifetimes.preferred_until(),
+            Some(now + Duration::from_secs(1800))
+        );
+    }
+
+    #[test]
+    fn link_notifications_update_or_remove_interfaces() {
+        let mut list = build_list(
+            vec![link(1, IFF_UP, "eth0"), link(2, IFF_UP, "eth1")],
+            vec![addr(1, 1)],
+        )
+        .unwrap();
+
+        let renamed = link_payload(1, IFF_UP, "wan0", &[2, 0, 0, 0, 0, 9]);
+        assert_eq!(apply(&mut list, RTM_NEWLINK, &renamed), Drained::Applied);
+        assert_eq!(list.0[&1].name, "wan0");
+        assert_eq!(list.0[&1].hw_addr, "02:00:00:00:00:09".parse().ok());
+        assert_eq!(list.0[&1].ips, vec![record(1)]);
+
+        let down = link_payload(1, 0, "wan0", &[]);
+        assert_eq!(apply(&mut list, RTM_NEWLINK, &down), Drained::Applied);
+        assert!(!list.0[&1].flags.up);
+        assert_eq!(list.0[&1].ips, vec![record(1)]);
+
+        let deleted = link_payload(1, 0, "wan0", &[]);
+        assert_eq!(apply(&mut list, RTM_DELLINK, &deleted), Drained::Applied);
+
+        let deleted = link_payload(2, 0, "eth1", &[]);
+        assert_eq!(apply(&mut list, RTM_DELLINK, &deleted), Drained::Applied);
+        assert!(list.0.is_empty());
+    }
+
+    #[test]
+    fn bridge_port_notifications_are_ignored() {
+        let mut list = build_list(vec![link(1, IFF_UP, "eth0")], vec![addr(1, 1)]).unwrap();
+        let before = list.clone();
+        // Enslaving and releasing a bridge port send AF_BRIDGE link messages for the port.
+        let mut port = link_payload(1, IFF_UP, "eth0", &[]);
+        port[0] = 7;
+
+        assert_eq!(apply(&mut list, RTM_NEWLINK, &port), Drained::Applied);
+        assert!(list == before);
+        assert_eq!(apply(&mut list, RTM_DELLINK, &port), Drained::Applied);
+        assert!(list == before);
+    }
+
+    #[test]
+    fn link_state_is_decoded() {
+        let interface = interface_from_link(link(1, IFF_UP | IFF_RUNNING | IFF_LOWER_UP, "eth0"));
+
+        assert!(interface.flags.up);
+        assert!(interface.flags.running);
+        assert!(interface.flags.lower_up);
+        assert!(!interface.flags.loopback);
+        assert_eq!(oper_state(6), OperState::Up);
+        assert_eq!(oper_state(3), OperState::LowerLayerDown);
+        assert_eq!(oper_state(42), OperState::Unknown);
+    }
+
+    #[test]
+    fn unknown_link_is_added_without_relist() {
+        let mut list = List::default();
+
+        let up = link_payload(3, IFF_UP, "veth0", &[]);
+
+        assert_eq!(apply(&mut list, RTM_NEWLINK, &up), Drained::Applied);
+        assert_eq!(list.0[&3].name, "veth0");
+        assert!(list.0[&3].ips.is_empty());
+    }
+
+    #[test]
+    fn down_links_keep_their_addresses() {
+        let list = build_list(
+            vec![link(1, IFF_UP, "up"), link(2, 0, "down")],
+            vec![addr(1, 1), addr(2, 2), addr(3, 3)],
+        )
+        .unwrap();
+
+        assert_eq!(list.0.len(), 2);
+        let interface = &list.0[&1];
+        assert_eq!(interface.name, "up");
+        assert_eq!(interface.hw_addr, "02:00:00:00:00:01".parse().ok());
+        assert_eq!(interface.ips, vec![record(1)]);
+        let interface = &list.0[&2];
+        assert!(!interface.flags.up);
+        assert_eq!(interface.ips, vec![record(2)]);
+    }
+}
@@ -1,28 +1,41 @@
-use std::fmt::Write;
-use std::{collections::HashMap, net::IpAddr};
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::IpAddr,
+    os::fd::{AsFd, BorrowedFd},
+};
 
 use nix::{
     ifaddrs::getifaddrs,
     net::if_::{if_nametoindex, InterfaceFlags},
+    sys::socket::{socket, AddressFamily, LinkAddr, SockFlag, SockType, SockaddrStorage},
 };
 
-use crate::{Error, Interface, IpRecord, List};
+use crate::{
+    AddrInfo, Error, HardwareAddr, Interface, InterfaceKind, InterfaceStats, IpRecord, LinkDetails,
+    LinkFlags, List,
+};
 
 struct CandidateInterface {
     name: String,
     index: u32,
     flags: InterfaceFlags,
-    hw_addr: Option<String>,
+    hw_addr: Option<HardwareAddr>,
+    kind: Option<InterfaceKind>,
     ips: Vec<CandidateIpRecord>,
 }
 
 struct CandidateIpRecord {
     pub ip: IpAddr,
     pub prefix_len: Option<u8>,
+    pub broadcast: Option<IpAddr>,
+    pub peer: Option<IpAddr>,
 }
 
 pub(crate) fn list_interfaces() -> Result<List, Error> {
     let addrs = getifaddrs().map_err(|e| Error::Getifaddrs(e.to_string()))?;
+    // getifaddrs does not report the MTU, so it is queried per interface with an ioctl. If no
+    // socket is available the MTU is simply left unknown.
+    let ioctl_socket = socket(AddressFamily::Inet, SockType::Datagram, ioctl_flags(), None).ok();
     let mut candidates = HashMap::new();
 
     for addr in addrs {
@@ -35,13 +48,16 @@ pub(crate) fn list_interfaces() -> Result<List, Error> {
                 index,
                 flags: addr.flags,
                 hw_addr: None,
+                kind: None,
                 ips: vec![],
             });
         candidate.flags |= addr.flags;
         if let Some(a) = addr.address {
             if let Some(a) = a.as_link_addr() {
-                if let Some(raw_addr) = a.addr() {
-                    candidate.hw_addr = Some(format_mac(&raw_addr)?);
+                candidate.hw_addr = link_hw_addr(a);
+                #[cfg(target_os = "android")]
+                {
+                    candidate.kind = Some(crate::interface_kind(None, a.hatype()));
                 }
             }
             let (ip, prefix_len) = if let Some(a) = a.as_sockaddr_in() {
@@ -65,33 +81,58 @@ pub(crate) fn list_interfaces() -> Result<List, Error> {
             } else {
                 continue;
             };
-            candidate.ips.push(CandidateIpRecord { ip, prefix_len });
+            candidate.ips.push(CandidateIpRecord {
+                ip,
+                prefix_len,
+                broadcast: addr.broadcast.as_ref().and_then(sockaddr_ip),
+                peer: addr.destination.as_ref().and_then(sockaddr_ip),
+            });
         }
     }
 
     let ifs = candidates
         .drain()
-        .filter(|(_, c)| c.flags.contains(InterfaceFlags::IFF_UP))
         .map(|(_, mut c)| {
             // alias IPs on Mac do not get their own prefix len
             apply_alias_prefix_fallback(&mut c.ips);
-            let ips = c
-                .ips
-                .iter()
-                .flat_map(|cip| {
-                    cip.prefix_len.map(|pl| IpRecord {
-                        ip: cip.ip,
-                        prefix_len: pl,
-                    })
-                })
-                .collect();
-            // MAC suppressed on Android
-            let hw_addr = c.hw_addr.unwrap_or_else(|| "00:00:00:00:00:00".to_string());
+            let mut ips = vec![];
+            let mut addr_info: BTreeMap<IpRecord, AddrInfo> = BTreeMap::new();
+            for cip in &c.ips {
+                let Some(prefix_len) = cip.prefix_len else {
+                    continue;
+                };
+                let record = IpRecord {
+                    ip: cip.ip,
+                    prefix_len,
+                };
+                // getifaddrs lists a local address once for each of its peers.
+                let info = addr_info.entry(record.clone()).or_default();
+                info.broadcast = cip.broadcast;
+                info.peers.extend(cip.peer);
+                info.peers.sort();
+                info.peers.dedup();
+                ips.push(record);
+            }
+            let mtu = ioctl_socket
+                .as_ref()
+                .and_then(|socket| interface_mtu(socket.as_fd(), &c.name));
             let mut interface = Interface {
                 index: c.index,
-                hw_addr,
+                hw_addr: c.hw_addr,
+                perm_hw_addr: None,
                 name: c.name,
+                alt_names: Vec::new(),
+                alias: None,
                 ips,
+                addr_info,
+                flags: link_flags(c.flags),
+                oper_state: None,
+                carrier: None,
+                mtu,
+                kind: c.kind,
+                master: None,
+                link: None,
+                link_netnsid: None,
             };
             interface.normalise();
             (c.index, interface)
@@ -100,6 +141,79 @@ pub(crate) fn list_interfaces() -> Result<List, Error> {
     Ok(List(ifs))
 }
 
+// SIOCGIFMTU is only exported by libc on some targets. This is _IOWR('i', 51, struct ifreq).
+#[cfg(target_os = "android")]
+const SIOCGIFMTU: nix::libc::c_ulong = nix::libc::SIOCGIFMTU;
+#[cfg(any(target_vendor = "apple", target_os = "freebsd"))]
+const SIOCGIFMTU: nix::libc::c_ulong = 0xc020_6933;
+
+// Apple platforms have no `SOCK_CLOEXEC`, so there the socket is left inheritable for the short
+// time it is open.
+#[cfg(not(target_vendor = "apple"))]
+fn ioctl_flags() -> SockFlag {
+    SockFlag::SOCK_CLOEXEC
+}
+
+#[cfg(target_vendor = "apple")]
+fn ioctl_flags() -> SockFlag {
+    SockFlag::empty()
+}
+
+#[cfg(any(target_os = "android", target_vendor = "apple", target_os = "freebsd"))]
+fn interface_mtu(socket: BorrowedFd<'_>, name: &str) -> Option<u32> {
+    use std::os::fd::AsRawFd;
+
+    let mut req = crate::ifreq::ifreq_for(name)?;
+    let res = unsafe { nix::libc::ioctl(socket.as_raw_fd(), SIOCGIFMTU as _, &mut req) };
+    if res < 0 {
+        return None;
+    }
+    u32::try_from(unsafe { req.ifr_ifru.ifru_mtu }).ok()
+}
+
+#[cfg(not(any(target_os = "android", target_vendor = "apple", target_os = "freebsd")))]
+fn interface_mtu(_socket: BorrowedFd<'_>, _name: &str) -> Option<u32> {
+    None
+}
+
+// `LinkAddr::addr` always returns six bytes whatever the address length, so the address is read
+// from the raw sockaddr instead. Links without an address, such as tun devices, have length 0.
+#[cfg(target_os = "android")]
+fn link_hw_addr(addr: &LinkAddr) -> Option<HardwareAddr> {
+    let sll: &nix::libc::sockaddr_ll = addr.as_ref();
+    let len = addr.halen().min(sll.sll_addr.len());
+    HardwareAddr::from_bytes(&sll.sll_addr[..len])
+}
+
+#[cfg(not(target_os = "android"))]
+fn link_hw_addr(addr: &LinkAddr) -> Option<HardwareAddr> {
+    let sdl: &nix::libc::sockaddr_dl = addr.as_ref();
+    // The address follows the interface name in `sdl_data`.
+    let data = sdl.sdl_data.get(addr.nlen()..addr.nlen() + addr.alen())?;
+    let bytes: Vec<u8> = data.iter().map(|&byte| byte as u8).collect();
+    HardwareAddr::from_bytes(&bytes)
+}
+
+fn sockaddr_ip(addr: &SockaddrStorage) -> Option<IpAddr> {
+    if let Some(addr) = addr.as_sockaddr_in() {
+        Some(IpAddr::V4(addr.ip()))
+    } else {
+        addr.as_sockaddr_in6().map(|addr| IpAddr::V6(addr.ip()))
+    }
+}
+
+fn link_flags(flags: InterfaceFlags) -> LinkFlags {
+    LinkFlags {
+        up: flags.contains(InterfaceFlags::IFF_UP),
+        running: flags.contains(InterfaceFlags::IFF_RUNNING),
+        lower_up: false,
+        loopback: flags.contains(InterfaceFlags::IFF_LOOPBACK),
+        point_to_point: flags.contains(InterfaceFlags::IFF_POINTOPOINT),
+        multicast: flags.contains(InterfaceFlags::IFF_MULTICAST),
+        broadcast: flags.contains(InterfaceFlags::IFF_BROADCAST),
+    }
+}
+
 // On macOS, alias addresses are not reported with their own netmask. Borrow a
 // prefix length from another address of the same family on the interface so
 // that aliases are not dropped. The fallback is applied per address family so
@@ -125,15 +239,14 @@ fn apply_fallback_for_family(
     }
 }
 
-fn format_mac(bytes: &[u8]) -> Result<String, Error> {
-    let mut mac = String::with_capacity(bytes.len() * 3);
-    for (i, b) in bytes.iter().enumerate() {
-        if i != 0 {
-            write!(mac, ":").map_err(|_| Error::FormatMacAddress)?;
-        }
-        write!(mac, "{b:02X}").map_err(|_| Error::FormatMacAddress)?;
-    }
-    Ok(mac)
+/// Traffic counters are only implemented for Linux, where they are read from rtnetlink.
+pub(crate) fn interface_stats() -> Result<HashMap<u32, InterfaceStats>, Error> {
+    Err(Error::Unsupported)
+}
+
+/// Link details are only implemented for Linux, where they are read from sysfs.
+pub(crate) fn link_details() -> Result<HashMap<u32, LinkDetails>, Error> {
+    Err(Error::Unsupported)
 }
 
 #[cfg(test)]
@@ -142,7 +255,12 @@ mod tests {
     use std::net::{Ipv4Addr, Ipv6Addr};
 
     fn record(ip: IpAddr, prefix_len: Option<u8>) -> CandidateIpRecord {
-        CandidateIpRecord { ip, prefix_len }
+        CandidateIpRecord {
+            ip,
+            prefix_len,
+            broadcast: None,
+            peer: None,
+        }
     }
 
     #[test]
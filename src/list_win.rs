@@ -1,5 +1,4 @@
-use std::collections::HashMap;
-use std::fmt::Write;
+use std::collections::{BTreeMap, HashMap};
 use std::net::IpAddr;
 use windows::Win32::Foundation::{
     ERROR_ADDRESS_NOT_ASSOCIATED, ERROR_BUFFER_OVERFLOW, ERROR_INVALID_PARAMETER,
@@ -11,14 +10,29 @@ use windows::Win32::NetworkManagement::IpHelper::{
 use windows::Win32::NetworkManagement::IpHelper::{
     GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_MULTICAST, IP_ADAPTER_ADDRESSES_LH,
 };
-use windows::Win32::NetworkManagement::Ndis::IfOperStatusDown;
+use windows::Win32::NetworkManagement::Ndis::{
+    IfOperStatusDormant, IfOperStatusDown, IfOperStatusLowerLayerDown, IfOperStatusNotPresent,
+    IfOperStatusTesting, IfOperStatusUp, IF_OPER_STATUS,
+};
 use windows::Win32::Networking::WinSock::{
     AF_INET, AF_INET6, AF_UNSPEC, SOCKADDR, SOCKADDR_IN, SOCKADDR_IN6,
 };
 
-use crate::{Error, Interface, IpRecord, List};
+use crate::{
+    Error, HardwareAddr, Interface, InterfaceStats, IpRecord, LinkDetails, LinkFlags, List,
+    OperState,
+};
 use aligned_vec::{AVec, ConstAlign};
 
+// Interface types from ipifcons.h
+const IF_TYPE_ETHERNET_CSMACD: u32 = 6;
+const IF_TYPE_PPP: u32 = 23;
+const IF_TYPE_SOFTWARE_LOOPBACK: u32 = 24;
+const IF_TYPE_IEEE80211: u32 = 71;
+const IF_TYPE_TUNNEL: u32 = 131;
+
+const IP_ADAPTER_NO_MULTICAST: u32 = 0x10;
+
 pub(crate) fn list_interfaces() -> Result<List, Error> {
     let mut ifs = HashMap::new();
     // Microsoft recommends a 15 KB initial buffer
@@ -61,18 +75,10 @@ pub(crate) fn list_interfaces() -> Result<List, Error> {
         let mut adapter_ptr = buf.as_ptr() as *const IP_ADAPTER_ADDRESSES_LH;
         while !adapter_ptr.is_null() {
             let adapter = &*adapter_ptr as &IP_ADAPTER_ADDRESSES_LH;
-            if adapter.OperStatus == IfOperStatusDown {
-                adapter_ptr = adapter.Next;
-                continue;
-            }
-            let mut hw_addr = String::with_capacity(adapter.PhysicalAddressLength as usize * 3);
-            for i in 0..adapter.PhysicalAddressLength as usize {
-                if i != 0 {
-                    write!(hw_addr, ":").map_err(|_| Error::FormatMacAddress)?;
-                }
-                write!(hw_addr, "{:02X}", adapter.PhysicalAddress[i])
-                    .map_err(|_| Error::FormatMacAddress)?;
-            }
+            let hw_addr = adapter
+                .PhysicalAddress
+                .get(..adapter.PhysicalAddressLength as usize)
+                .and_then(HardwareAddr::from_bytes);
             let mut ips = vec![];
             let mut unicast_ptr = adapter.FirstUnicastAddress;
             while !unicast_ptr.is_null() {
@@ -114,8 +120,20 @@ pub(crate) fn list_interfaces() -> Result<List, Error> {
             let mut iface = Interface {
                 index: ifindex,
                 name,
+                alt_names: Vec::new(),
+                alias: None,
                 hw_addr,
+                perm_hw_addr: None,
                 ips,
+                addr_info: BTreeMap::new(),
+                flags: link_flags(adapter),
+                oper_state: Some(oper_state(adapter.OperStatus)),
+                carrier: None,
+                mtu: Some(adapter.Mtu),
+                kind: None,
+                master: None,
+                link: None,
+                link_netnsid: None,
             };
             iface.normalise();
             ifs.insert(ifindex, iface);
@@ -126,6 +144,45 @@ pub(crate) fn list_interfaces() -> Result<List, Error> {
     Ok(List(ifs))
 }
 
+// Windows has no direct equivalent of the Unix interface flags, so approximate them from the
+// adapter's type and status.
+fn link_flags(adapter: &IP_ADAPTER_ADDRESSES_LH) -> LinkFlags {
+    let adapter_flags = unsafe { adapter.Anonymous2.Flags };
+    LinkFlags {
+        up: adapter.OperStatus != IfOperStatusDown,
+        running: adapter.OperStatus == IfOperStatusUp,
+        lower_up: false,
+        loopback: adapter.IfType == IF_TYPE_SOFTWARE_LOOPBACK,
+        point_to_point: adapter.IfType == IF_TYPE_PPP || adapter.IfType == IF_TYPE_TUNNEL,
+        multicast: adapter_flags & IP_ADAPTER_NO_MULTICAST == 0,
+        broadcast: adapter.IfType == IF_TYPE_ETHERNET_CSMACD || adapter.IfType == IF_TYPE_IEEE80211,
+    }
+}
+
+fn oper_state(status: IF_OPER_STATUS) -> OperState {
+    [
+        (IfOperStatusUp, OperState::Up),
+        (IfOperStatusDown, OperState::Down),
+        (IfOperStatusTesting, OperState::Testing),
+        (IfOperStatusDormant, OperState::Dormant),
+        (IfOperStatusNotPresent, OperState::NotPresent),
+        (IfOperStatusLowerLayerDown, OperState::LowerLayerDown),
+    ]
+    .into_iter()
+    .find(|&(value, _)| value == status)
+    .map_or(OperState::Unknown, |(_, state)| state)
+}
+
+/// Traffic counters are only implemented for Linux, where they are read from rtnetlink.
+pub(crate) fn interface_stats() -> Result<HashMap<u32, InterfaceStats>, Error> {
+    Err(Error::Unsupported)
+}
+
+/// Link details are only implemented for Linux, where they are read from sysfs.
+pub(crate) fn link_details() -> Result<HashMap<u32, LinkDetails>, Error> {
+    Err(Error::Unsupported)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
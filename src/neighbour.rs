@@ -0,0 +1,221 @@
+use std::cmp::Ordering;
+use std::net::IpAddr;
+
+#[cfg(any(target_os = "linux", test))]
+use crate::keyed::{Keyed, KeyedCursor, KeyedDiff, KeyedList};
+use crate::HardwareAddr;
+
+/// An entry in the kernel's neighbour table, which maps the IP addresses of hosts on attached
+/// links to their link-layer addresses. IPv4 entries are learned with ARP and IPv6 entries with
+/// neighbour discovery.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Neighbour {
+    /// The neighbour's IP address.
+    pub ip: IpAddr,
+    /// The neighbour's link-layer address. `None` while it is still being resolved, after
+    /// resolution failed, or on links that have no link-layer addresses.
+    pub hw_addr: Option<HardwareAddr>,
+    /// Index of the interface the neighbour is reached through.
+    pub ifindex: u32,
+    /// How recently the neighbour was confirmed to be reachable.
+    pub state: NeighbourState,
+    /// Whether the neighbour has advertised itself as an IPv6 router.
+    pub is_router: bool,
+}
+
+/// Neighbours are ordered by interface index and then IP address, as in `NeighbourUpdate`.
+impl Ord for Neighbour {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (
+            self.ifindex,
+            self.ip,
+            &self.hw_addr,
+            self.state,
+            self.is_router,
+        )
+            .cmp(&(
+                other.ifindex,
+                other.ip,
+                &other.hw_addr,
+                other.state,
+                other.is_router,
+            ))
+    }
+}
+
+impl PartialOrd for Neighbour {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The reachability of a neighbour, from the kernel's `NUD_*` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum NeighbourState {
+    /// The link-layer address is being resolved.
+    Incomplete,
+    /// The neighbour was recently confirmed to be reachable.
+    Reachable,
+    /// The link-layer address is known, but has not been confirmed recently. It will be checked
+    /// the next time traffic is sent to the neighbour.
+    Stale,
+    /// Traffic was sent to a stale neighbour, and confirmation from upper layers is awaited
+    /// before probing.
+    Delay,
+    /// The neighbour is being probed to confirm it is still reachable.
+    Probe,
+    /// The neighbour did not respond to resolution or probing.
+    Failed,
+    /// No resolution is needed, e.g. for a multicast address.
+    NoArp,
+    /// The entry was configured statically and does not expire.
+    Permanent,
+    /// A state value with no dedicated variant.
+    Other(u16),
+}
+
+/// Information delivered when the neighbour table changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NeighbourUpdate {
+    /// Whether this update represents the initial neighbour table.
+    pub is_initial: bool,
+    /// Every current neighbour, sorted by interface index and then IP address.
+    pub neighbours: Vec<Neighbour>,
+    /// The changes from the preceding update to `neighbours`.
+    pub diff: NeighbourDiff,
+}
+
+/// What changed between one `NeighbourUpdate` and the next.
+///
+/// For an initial update, every current neighbour is listed as added.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NeighbourDiff {
+    /// Neighbours that appeared.
+    pub added: Vec<Neighbour>,
+    /// Neighbours that disappeared, containing their last known state.
+    pub removed: Vec<Neighbour>,
+    /// Neighbours whose link-layer address, state or router flag changed.
+    pub modified: Vec<ModifiedNeighbour>,
+}
+
+/// The previous and current state of a neighbour that was modified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModifiedNeighbour {
+    pub before: Neighbour,
+    pub after: Neighbour,
+}
+
+#[cfg(any(target_os = "linux", test))]
+impl Keyed for Neighbour {
+    type Key = (u32, IpAddr);
+    type Update = NeighbourUpdate;
+
+    fn key(&self) -> Self::Key {
+        (self.ifindex, self.ip)
+    }
+
+    fn update(
+        is_initial: bool,
+        neighbours: Vec<Neighbour>,
+        diff: KeyedDiff<Neighbour>,
+    ) -> NeighbourUpdate {
+        NeighbourUpdate {
+            is_initial,
+            neighbours,
+            diff: NeighbourDiff {
+                added: diff.added,
+                removed: diff.removed,
+                modified: diff
+                    .modified
+                    .into_iter()
+                    .map(|(before, after)| ModifiedNeighbour { before, after })
+                    .collect(),
+            },
+        }
+    }
+}
+
+/// A snapshot of the neighbour table. Its order puts the key first, so each interface index and
+/// IP address appears at most once and can be found by binary search.
+#[cfg(any(target_os = "linux", test))]
+pub(crate) type NeighbourList = KeyedList<Neighbour>;
+
+#[cfg(any(target_os = "linux", test))]
+pub(crate) type NeighbourCursor = KeyedCursor<Neighbour>;
+
+#[cfg(any(target_os = "linux", test))]
+impl NeighbourList {
+    pub(crate) fn insert(&mut self, neighbour: Neighbour) {
+        match self.position(neighbour.key()) {
+            Ok(index) => self.0[index] = neighbour,
+            Err(index) => self.0.insert(index, neighbour),
+        }
+    }
+
+    pub(crate) fn remove(&mut self, ifindex: u32, ip: IpAddr) {
+        if let Ok(index) = self.position((ifindex, ip)) {
+            self.0.remove(index);
+        }
+    }
+
+    fn position(&self, key: (u32, IpAddr)) -> Result<usize, usize> {
+        self.0
+            .binary_search_by(|neighbour| neighbour.key().cmp(&key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    fn neighbour(last_octet: u8, mac: &str, state: NeighbourState) -> Neighbour {
+        Neighbour {
+            ip: IpAddr::V4(Ipv4Addr::new(192, 0, 2, last_octet)),
+            hw_addr: Some(mac.parse().unwrap()),
+            ifindex: 2,
+            state,
+            is_router: false,
+        }
+    }
+
+    fn list(neighbours: &[Neighbour]) -> NeighbourList {
+        let mut list = NeighbourList::default();
+        for neighbour in neighbours {
+            list.insert(neighbour.clone());
+        }
+        list
+    }
+
+    #[test]
+    fn changed_mac_is_a_modification() {
+        let gateway = neighbour(1, "02:00:00:00:00:01", NeighbourState::Reachable);
+        let peer = neighbour(20, "02:00:00:00:00:20", NeighbourState::Stale);
+        let mut cursor = NeighbourCursor::default();
+
+        let initial = cursor
+            .advance(list(&[peer.clone(), gateway.clone()]))
+            .unwrap();
+        assert!(initial.is_initial);
+        assert_eq!(initial.neighbours, vec![gateway.clone(), peer.clone()]);
+        assert_eq!(initial.diff.added, initial.neighbours);
+
+        let spoofed = neighbour(1, "02:00:00:00:00:99", NeighbourState::Reachable);
+        let mut next = list(&[gateway.clone(), peer.clone()]);
+        next.insert(spoofed.clone());
+        next.remove(2, peer.ip);
+        let update = cursor.advance(next.clone()).unwrap();
+
+        assert!(!update.is_initial);
+        assert!(update.diff.added.is_empty());
+        assert_eq!(update.diff.removed, vec![peer]);
+        assert_eq!(
+            update.diff.modified,
+            vec![ModifiedNeighbour {
+                before: gateway,
+                after: spoofed,
+            }]
+        );
+        assert_eq!(cursor.advance(next), None);
+    }
+}
@@ -0,0 +1,137 @@
+use std::os::fd::{AsRawFd, BorrowedFd, OwnedFd};
+
+use nix::errno::Errno;
+use nix::sys::socket::{recv, MsgFlags};
+
+use crate::neighbour::{NeighbourCursor, NeighbourList};
+use crate::netlink::{
+    messages, parse_neigh, NeighMessage, RouteSocket, NTF_ROUTER, RTM_DELNEIGH, RTM_NEWNEIGH,
+};
+use crate::watch::{open_netlink_event_socket, RTMGRP_NEIGH};
+use crate::watch_fd::{Drained, EventSocket, EventSocketOps, Snapshot, WatchHandle};
+use crate::{Error, HardwareAddr, Neighbour, NeighbourState, NeighbourUpdate, WatchOptions};
+
+pub(crate) type AsyncNeighbourWatch = crate::watch_fd::AsyncWatch<NeighbourList>;
+pub(crate) type BlockingNeighbourWatch = crate::watch_fd::BlockingWatch<NeighbourList>;
+
+const EVENT_SOCKET_OPS: EventSocketOps<NeighbourList> = EventSocketOps {
+    open: open_event_socket,
+    drain: drain_event_socket,
+};
+
+pub(crate) fn list_neighbours() -> Result<Vec<Neighbour>, Error> {
+    fetch_neighbours().map(|list| list.0)
+}
+
+fn fetch_neighbours() -> Result<NeighbourList, Error> {
+    let neighbours = RouteSocket::open()?
+        .dump_neighbours()?
+        .into_iter()
+        .map(neighbour_from_message)
+        .collect();
+    Ok(NeighbourList::new(neighbours))
+}
+
+impl Snapshot for NeighbourList {
+    type Update = NeighbourUpdate;
+    type Cursor = NeighbourCursor;
+
+    fn fetch(_options: &WatchOptions) -> Result<Self, Error> {
+        fetch_neighbours()
+    }
+
+    fn cursor(_options: &WatchOptions) -> NeighbourCursor {
+        NeighbourCursor::default()
+    }
+
+    fn advance(cursor: &mut NeighbourCursor, list: NeighbourList) -> Option<NeighbourUpdate> {
+        cursor.advance(list)
+    }
+}
+
+pub(crate) fn watch_neighbours_with_callback<F: FnMut(NeighbourUpdate) + Send + 'static>(
+    callback: F,
+    options: &WatchOptions,
+) -> Result<WatchHandle, Error> {
+    crate::watch_fd::watch_with_callback(callback, EVENT_SOCKET_OPS, options)
+}
+
+pub(crate) fn watch_neighbours_async<A: crate::async_adapter::AsyncFdAdapter>(
+    options: &WatchOptions,
+) -> Result<AsyncNeighbourWatch, Error> {
+    crate::watch_fd::watch_async::<NeighbourList, A>(EVENT_SOCKET_OPS, options)
+}
+
+pub(crate) fn watch_neighbours_blocking(
+    options: &WatchOptions,
+) -> Result<BlockingNeighbourWatch, Error> {
+    crate::watch_fd::watch_blocking(EVENT_SOCKET_OPS, options)
+}
+
+fn open_event_socket(options: &WatchOptions) -> Result<EventSocket, Error> {
+    open_netlink_event_socket(options, RTMGRP_NEIGH).map(EventSocket::from)
+}
+
+// Neighbour entries change state often, so unlike routes they are applied one notification at a
+// time instead of dumping the whole table again.
+fn drain_event_socket(
+    fd: BorrowedFd<'_>,
+    _sources: &[OwnedFd],
+    list: &mut NeighbourList,
+) -> Drained {
+    let mut buf = [0u8; 8192];
+    let mut drained = Drained::Applied;
+    loop {
+        match recv(fd.as_raw_fd(), &mut buf, MsgFlags::MSG_TRUNC) {
+            Ok(0) => break,
+            // A truncated datagram has lost notifications
+            Ok(len) if len > buf.len() => drained = drained.max(Drained::Relist),
+            Ok(len) => {
+                for message in messages(&buf[..len]) {
+                    let Some(neigh) = parse_neigh(message.payload) else {
+                        continue;
+                    };
+                    match message.kind {
+                        RTM_NEWNEIGH => list.insert(neighbour_from_message(neigh)),
+                        RTM_DELNEIGH => list.remove(neigh.index, neigh.ip),
+                        _ => {}
+                    }
+                }
+            }
+            Err(Errno::EAGAIN) => break,
+            Err(Errno::ENOBUFS) => drained = Drained::Overrun,
+            Err(_) => {
+                drained = drained.max(Drained::Relist);
+                break;
+            }
+        }
+    }
+    drained
+}
+
+fn neighbour_from_message(neigh: NeighMessage) -> Neighbour {
+    Neighbour {
+        ip: neigh.ip,
+        hw_addr: neigh
+            .lladdr
+            .and_then(|lladdr| HardwareAddr::from_bytes(&lladdr)),
+        ifindex: neigh.index,
+        state: neighbour_state(neigh.state),
+        is_router: neigh.flags & NTF_ROUTER != 0,
+    }
+}
+
+// NUD_* values from neighbour.h
+fn neighbour_state(value: u16) -> NeighbourState {
+    match value {
+        0x01 => NeighbourState::Incomplete,
+        0x02 => NeighbourState::Reachable,
+        0x04 => NeighbourState::Stale,
+        0x08 => NeighbourState::Delay,
+        0x10 => NeighbourState::Probe,
+        0x20 => NeighbourState::Failed,
+        0x40 => NeighbourState::NoArp,
+        0x80 => NeighbourState::Permanent,
+        other => NeighbourState::Other(other),
+    }
+}
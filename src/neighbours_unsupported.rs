@@ -0,0 +1,50 @@
+use crate::{Error, Neighbour, NeighbourUpdate, WatchOptions, WatchStats};
+
+/// Neighbour watchers cannot be created on this platform, so these types have no values.
+pub(crate) enum AsyncNeighbourWatch {}
+pub(crate) enum BlockingNeighbourWatch {}
+
+impl AsyncNeighbourWatch {
+    pub(crate) async fn changed(&mut self) -> NeighbourUpdate {
+        match *self {}
+    }
+
+    pub(crate) fn stats(&self) -> WatchStats {
+        match *self {}
+    }
+}
+
+impl BlockingNeighbourWatch {
+    pub(crate) fn changed(&mut self) -> NeighbourUpdate {
+        match *self {}
+    }
+
+    pub(crate) fn stats(&self) -> WatchStats {
+        match *self {}
+    }
+}
+
+/// The neighbour table is only implemented for Linux, where it is read from rtnetlink.
+pub(crate) fn list_neighbours() -> Result<Vec<Neighbour>, Error> {
+    Err(Error::Unsupported)
+}
+
+pub(crate) fn watch_neighbours_with_callback<F: FnMut(NeighbourUpdate) + Send + 'static>(
+    _callback: F,
+    _options: &WatchOptions,
+) -> Result<crate::watch::WatchHandle, Error> {
+    Err(Error::Unsupported)
+}
+
+#[allow(clippy::extra_unused_type_parameters)]
+pub(crate) fn watch_neighbours_async<A: crate::async_adapter::AsyncFdAdapter>(
+    _options: &WatchOptions,
+) -> Result<AsyncNeighbourWatch, Error> {
+    Err(Error::Unsupported)
+}
+
+pub(crate) fn watch_neighbours_blocking(
+    _options: &WatchOptions,
+) -> Result<BlockingNeighbourWatch, Error> {
+    Err(Error::Unsupported)
+}
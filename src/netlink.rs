@@ -0,0 +1,1302 @@
+//! Minimal rtnetlink support used by the Linux lister and watcher.
+//!
+//! Messages are decoded directly from byte buffers so that no extra dependency is needed and
+//! so that truncated or malformed input is skipped rather than read out of bounds.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::os::fd::{AsRawFd, OwnedFd};
+
+use nix::errno::Errno;
+use nix::sys::socket::{
+    recv, sendto, socket, AddressFamily, MsgFlags, NetlinkAddr, SockFlag, SockProtocol, SockType,
+};
+
+use crate::Error;
+
+const NLMSG_HDRLEN: usize = 16;
+const NLMSG_ERROR: u16 = 2;
+const NLMSG_DONE: u16 = 3;
+
+const NLM_F_REQUEST: u16 = 0x01;
+const NLM_F_DUMP_INTR: u16 = 0x10;
+pub(crate) const NLM_F_REPLACE: u16 = 0x100;
+const NLM_F_DUMP: u16 = 0x300;
+
+const NLA_TYPE_MASK: u16 = 0x3fff;
+
+pub(crate) const RTM_NEWLINK: u16 = 16;
+pub(crate) const RTM_DELLINK: u16 = 17;
+pub(crate) const RTM_GETLINK: u16 = 18;
+pub(crate) const RTM_NEWADDR: u16 = 20;
+pub(crate) const RTM_DELADDR: u16 = 21;
+pub(crate) const RTM_GETADDR: u16 = 22;
+pub(crate) const RTM_NEWROUTE: u16 = 24;
+pub(crate) const RTM_DELROUTE: u16 = 25;
+const RTM_GETROUTE: u16 = 26;
+pub(crate) const RTM_NEWNEIGH: u16 = 28;
+pub(crate) const RTM_DELNEIGH: u16 = 29;
+const RTM_GETNEIGH: u16 = 30;
+const RTM_NEWRULE: u16 = 32;
+const RTM_GETRULE: u16 = 34;
+pub(crate) const RTM_NEWNDUSEROPT: u16 = 68;
+
+const IFINFOMSG_LEN: usize = 16;
+const IFADDRMSG_LEN: usize = 8;
+const RTMSG_LEN: usize = 12;
+const NDMSG_LEN: usize = 12;
+const FIB_RULE_HDR_LEN: usize = 12;
+const NDUSEROPTMSG_LEN: usize = 16;
+const RTNEXTHOP_LEN: usize = 8;
+
+const IFLA_ADDRESS: u16 = 1;
+const IFLA_IFNAME: u16 = 3;
+const IFLA_MTU: u16 = 4;
+const IFLA_LINK: u16 = 5;
+const IFLA_MASTER: u16 = 10;
+const IFLA_OPERSTATE: u16 = 16;
+const IFLA_LINKINFO: u16 = 18;
+const IFLA_IFALIAS: u16 = 20;
+const IFLA_STATS64: u16 = 23;
+const IFLA_CARRIER: u16 = 33;
+const IFLA_LINK_NETNSID: u16 = 37;
+const IFLA_PROP_LIST: u16 = 52;
+const IFLA_ALT_IFNAME: u16 = 53;
+const IFLA_PERM_ADDRESS: u16 = 54;
+
+const IFLA_INFO_KIND: u16 = 1;
+
+const IFA_ADDRESS: u16 = 1;
+const IFA_LOCAL: u16 = 2;
+const IFA_LABEL: u16 = 3;
+const IFA_BROADCAST: u16 = 4;
+const IFA_CACHEINFO: u16 = 6;
+const IFA_FLAGS: u16 = 8;
+
+const RTA_DST: u16 = 1;
+const RTA_OIF: u16 = 4;
+const RTA_GATEWAY: u16 = 5;
+const RTA_PRIORITY: u16 = 6;
+const RTA_PREFSRC: u16 = 7;
+const RTA_MULTIPATH: u16 = 9;
+const RTA_TABLE: u16 = 15;
+
+const RTM_F_CLONED: u32 = 0x200;
+
+const NDA_DST: u16 = 1;
+const NDA_LLADDR: u16 = 2;
+
+const FRA_DST: u16 = 1;
+const FRA_SRC: u16 = 2;
+const FRA_IIFNAME: u16 = 3;
+const FRA_GOTO: u16 = 4;
+const FRA_PRIORITY: u16 = 6;
+const FRA_FWMARK: u16 = 10;
+const FRA_TUN_ID: u16 = 12;
+const FRA_SUPPRESS_PREFIXLEN: u16 = 14;
+const FRA_TABLE: u16 = 15;
+const FRA_FWMASK: u16 = 16;
+const FRA_OIFNAME: u16 = 17;
+const FRA_L3MDEV: u16 = 19;
+const FRA_UID_RANGE: u16 = 20;
+const FRA_IP_PROTO: u16 = 22;
+const FRA_SPORT_RANGE: u16 = 23;
+const FRA_DPORT_RANGE: u16 = 24;
+const FRA_DSCP: u16 = 25;
+const FRA_FLOWLABEL: u16 = 26;
+
+const AF_UNSPEC: u8 = 0;
+const AF_INET: u8 = 2;
+const AF_INET6: u8 = 10;
+
+pub(crate) const NTF_ROUTER: u8 = 0x80;
+
+pub(crate) const ND_ROUTER_ADVERT: u8 = 134;
+
+pub(crate) const FR_ACT_TO_TBL: u8 = 1;
+pub(crate) const FR_ACT_GOTO: u8 = 2;
+pub(crate) const FR_ACT_NOP: u8 = 3;
+pub(crate) const FR_ACT_BLACKHOLE: u8 = 6;
+pub(crate) const FR_ACT_UNREACHABLE: u8 = 7;
+pub(crate) const FR_ACT_PROHIBIT: u8 = 8;
+
+pub(crate) const FIB_RULE_INVERT: u32 = 0x2;
+
+pub(crate) const IFF_UP: u32 = 0x1;
+pub(crate) const IFF_BROADCAST: u32 = 0x2;
+pub(crate) const IFF_LOOPBACK: u32 = 0x8;
+pub(crate) const IFF_POINTOPOINT: u32 = 0x10;
+pub(crate) const IFF_RUNNING: u32 = 0x40;
+pub(crate) const IFF_MULTICAST: u32 = 0x1000;
+pub(crate) const IFF_LOWER_UP: u32 = 0x10000;
+
+// IFA_F_SECONDARY and IFA_F_TEMPORARY share a value. It means secondary for IPv4 and temporary
+// for IPv6.
+pub(crate) const IFA_F_SECONDARY: u32 = 0x01;
+pub(crate) const IFA_F_TEMPORARY: u32 = 0x01;
+pub(crate) const IFA_F_OPTIMISTIC: u32 = 0x04;
+pub(crate) const IFA_F_DADFAILED: u32 = 0x08;
+pub(crate) const IFA_F_DEPRECATED: u32 = 0x20;
+pub(crate) const IFA_F_TENTATIVE: u32 = 0x40;
+pub(crate) const IFA_F_PERMANENT: u32 = 0x80;
+
+/// An address lifetime that never expires.
+pub(crate) const INFINITY_LIFE_TIME: u32 = u32::MAX;
+
+// Kernel dumps are packed into buffers of up to 32 KiB, so this is always large enough to
+// receive a complete datagram.
+const RECV_BUFFER_LEN: usize = 64 * 1024;
+
+// A dump that keeps being interrupted by concurrent changes is retried this many times before
+// giving up, so that a constantly churning system cannot stall the caller forever.
+const DUMP_ATTEMPTS: usize = 16;
+
+/// A single netlink message borrowed from a receive buffer.
+pub(crate) struct Message<'a> {
+    pub(crate) kind: u16,
+    pub(crate) flags: u16,
+    pub(crate) seq: u32,
+    pub(crate) payload: &'a [u8],
+}
+
+/// Iterate over the netlink messages packed into a datagram.
+pub(crate) fn messages(buf: &[u8]) -> impl Iterator<Item = Message<'_>> {
+    let mut rest = buf;
+    std::iter::from_fn(move || {
+        if rest.len() < NLMSG_HDRLEN {
+            return None;
+        }
+        let len = read_u32(rest, 0)? as usize;
+        if len < NLMSG_HDRLEN || len > rest.len() {
+            return None;
+        }
+        let message = Message {
+            kind: read_u16(rest, 4)?,
+            flags: read_u16(rest, 6)?,
+            seq: read_u32(rest, 8)?,
+            payload: &rest[NLMSG_HDRLEN..len],
+        };
+        rest = rest.get(align(len)..).unwrap_or_default();
+        Some(message)
+    })
+}
+
+/// Iterate over the `(type, data)` route attributes in a buffer.
+pub(crate) fn attributes(buf: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    let mut rest = buf;
+    std::iter::from_fn(move || {
+        let len = read_u16(rest, 0)? as usize;
+        let kind = read_u16(rest, 2)? & NLA_TYPE_MASK;
+        if len < 4 || len > rest.len() {
+            return None;
+        }
+        let data = &rest[4..len];
+        rest = rest.get(align(len)..).unwrap_or_default();
+        Some((kind, data))
+    })
+}
+
+/// The parts of an `RTM_NEWLINK` message that describe an interface.
+pub(crate) struct LinkMessage {
+    pub(crate) index: u32,
+    pub(crate) flags: u32,
+    /// ARPHRD_* hardware type.
+    pub(crate) arphrd: u16,
+    /// Driver kind from IFLA_INFO_KIND, e.g. "veth". Plain hardware devices have none.
+    pub(crate) kind: Option<String>,
+    pub(crate) name: Option<String>,
+    /// Alternative names from IFLA_PROP_LIST, in the order they were added.
+    pub(crate) alt_names: Vec<String>,
+    /// Free-form description from IFLA_IFALIAS.
+    pub(crate) alias: Option<String>,
+    pub(crate) hw_addr: Option<Vec<u8>>,
+    /// The address the device was manufactured with, from IFLA_PERM_ADDRESS.
+    pub(crate) perm_hw_addr: Option<Vec<u8>>,
+    pub(crate) oper_state: Option<u8>,
+    pub(crate) carrier: Option<bool>,
+    pub(crate) mtu: Option<u32>,
+    /// Index of the bridge or bond this link is enslaved to.
+    pub(crate) master: Option<u32>,
+    /// Index of the lower device, or of the peer for a veth.
+    pub(crate) link: Option<u32>,
+    /// Namespace id that `link` belongs to, when it is not the current namespace.
+    pub(crate) link_netnsid: Option<i32>,
+    pub(crate) stats: Option<LinkStats>,
+}
+
+/// The leading counters of `struct rtnl_link_stats64`, in kernel order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LinkStats {
+    pub(crate) rx_packets: u64,
+    pub(crate) tx_packets: u64,
+    pub(crate) rx_bytes: u64,
+    pub(crate) tx_bytes: u64,
+    pub(crate) rx_errors: u64,
+    pub(crate) tx_errors: u64,
+    pub(crate) rx_dropped: u64,
+    pub(crate) tx_dropped: u64,
+}
+
+fn parse_stats(data: &[u8]) -> Option<LinkStats> {
+    Some(LinkStats {
+        rx_packets: read_u64(data, 0)?,
+        tx_packets: read_u64(data, 8)?,
+        rx_bytes: read_u64(data, 16)?,
+        tx_bytes: read_u64(data, 24)?,
+        rx_errors: read_u64(data, 32)?,
+        tx_errors: read_u64(data, 40)?,
+        rx_dropped: read_u64(data, 48)?,
+        tx_dropped: read_u64(data, 56)?,
+    })
+}
+
+/// Parse a link message, skipping those of other families. Bridge port notifications use
+/// AF_BRIDGE and only describe part of an interface, even when they are an `RTM_DELLINK` for a
+/// port leaving its bridge.
+pub(crate) fn parse_link(payload: &[u8]) -> Option<LinkMessage> {
+    let header = payload.get(..IFINFOMSG_LEN)?;
+    if header[0] != AF_UNSPEC {
+        return None;
+    }
+    let mut link = LinkMessage {
+        index: read_u32(header, 4)?,
+        flags: read_u32(header, 8)?,
+        arphrd: read_u16(header, 2)?,
+        kind: None,
+        name: None,
+        alt_names: Vec::new(),
+        alias: None,
+        hw_addr: None,
+        perm_hw_addr: None,
+        oper_state: None,
+        carrier: None,
+        mtu: None,
+        master: None,
+        link: None,
+        link_netnsid: None,
+        stats: None,
+    };
+    for (kind, data) in attributes(&payload[IFINFOMSG_LEN..]) {
+        match kind {
+            IFLA_IFNAME => link.name = Some(read_string(data)),
+            IFLA_ADDRESS => link.hw_addr = Some(data.to_vec()),
+            IFLA_PERM_ADDRESS => link.perm_hw_addr = Some(data.to_vec()),
+            // Clearing the alias sends an empty attribute rather than omitting it.
+            IFLA_IFALIAS => link.alias = Some(read_string(data)).filter(|alias| !alias.is_empty()),
+            IFLA_PROP_LIST => {
+                link.alt_names = attributes(data)
+                    .filter(|&(kind, _)| kind == IFLA_ALT_IFNAME)
+                    .map(|(_, data)| read_string(data))
+                    .collect();
+            }
+            IFLA_OPERSTATE => link.oper_state = data.first().copied(),
+            IFLA_CARRIER => link.carrier = data.first().map(|&carrier| carrier != 0),
+            IFLA_MTU => link.mtu = read_u32(data, 0),
+            IFLA_MASTER => link.master = read_u32(data, 0).filter(|&index| index != 0),
+            IFLA_LINK => link.link = read_u32(data, 0).filter(|&index| index != 0),
+            IFLA_STATS64 => link.stats = parse_stats(data),
+            IFLA_LINK_NETNSID => link.link_netnsid = read_u32(data, 0).map(|id| id as i32),
+            IFLA_LINKINFO => {
+                link.kind = attributes(data)
+                    .find(|&(kind, _)| kind == IFLA_INFO_KIND)
+                    .map(|(_, data)| read_string(data));
+            }
+            _ => {}
+        }
+    }
+    Some(link)
+}
+
+/// The parts of an `RTM_NEWADDR` message that describe an address.
+pub(crate) struct AddrMessage {
+    pub(crate) index: u32,
+    pub(crate) ip: IpAddr,
+    pub(crate) prefix_len: u8,
+    /// The remote end of a point-to-point link.
+    pub(crate) peer: Option<IpAddr>,
+    pub(crate) broadcast: Option<IpAddr>,
+    /// RT_SCOPE_* value.
+    pub(crate) scope: u8,
+    /// IFA_LABEL, e.g. "eth0:1". Only IPv4 addresses have labels.
+    pub(crate) label: Option<String>,
+    /// IFA_F_* flags.
+    pub(crate) flags: u32,
+    /// Preferred and valid lifetimes in seconds, from IFA_CACHEINFO.
+    pub(crate) lifetimes: Option<(u32, u32)>,
+}
+
+pub(crate) fn parse_addr(payload: &[u8]) -> Option<AddrMessage> {
+    let header = payload.get(..IFADDRMSG_LEN)?;
+    let family = header[0];
+    let prefix_len = header[1];
+    // The header only has room for the low eight flags. IFA_FLAGS carries all of them.
+    let mut flags = u32::from(header[2]);
+    let scope = header[3];
+    let index = read_u32(header, 4)?;
+    let mut address = None;
+    let mut local = None;
+    let mut broadcast = None;
+    let mut label = None;
+    let mut lifetimes = None;
+    for (kind, data) in attributes(&payload[IFADDRMSG_LEN..]) {
+        match kind {
+            IFA_ADDRESS => address = read_ip(family, data),
+            IFA_LOCAL => local = read_ip(family, data),
+            IFA_BROADCAST => broadcast = read_ip(family, data),
+            IFA_LABEL => label = Some(read_string(data)),
+            IFA_FLAGS => flags = read_u32(data, 0).unwrap_or(flags),
+            IFA_CACHEINFO => lifetimes = read_u32(data, 0).zip(read_u32(data, 4)),
+            _ => {}
+        }
+    }
+    // On point-to-point links IFA_ADDRESS is the peer, so the local address takes precedence.
+    // Otherwise the two are the same.
+    let ip = local.or(address)?;
+    let peer = address.filter(|&address| address != ip);
+    Some(AddrMessage {
+        index,
+        ip,
+        prefix_len,
+        peer,
+        broadcast,
+        scope,
+        label,
+        flags,
+        lifetimes,
+    })
+}
+
+/// The parts of an `RTM_NEWROUTE` message that describe a route.
+pub(crate) struct RouteMessage {
+    pub(crate) destination: IpAddr,
+    pub(crate) prefix_len: u8,
+    /// RT_TABLE_* value, from RTA_TABLE when the table id does not fit in the header.
+    pub(crate) table: u32,
+    /// RTPROT_* value.
+    pub(crate) protocol: u8,
+    /// RTN_* value.
+    pub(crate) kind: u8,
+    pub(crate) gateway: Option<IpAddr>,
+    pub(crate) oif: Option<u32>,
+    pub(crate) priority: Option<u32>,
+    pub(crate) prefsrc: Option<IpAddr>,
+    /// The next hops from RTA_MULTIPATH, in kernel order.
+    pub(crate) nexthops: Vec<NextHopMessage>,
+    /// Whether this is a cached exception, such as a learned path MTU, rather than a route in
+    /// the table. Dumps leave these out, but they are announced like any other route.
+    pub(crate) cloned: bool,
+}
+
+/// One `struct rtnexthop` of a multipath route.
+pub(crate) struct NextHopMessage {
+    pub(crate) gateway: Option<IpAddr>,
+    pub(crate) oif: u32,
+    /// The weight minus one.
+    pub(crate) hops: u8,
+}
+
+pub(crate) fn parse_route(payload: &[u8]) -> Option<RouteMessage> {
+    let header = payload.get(..RTMSG_LEN)?;
+    let family = header[0];
+    // A route without RTA_DST covers the whole address space, e.g. the default route.
+    let mut destination = match family {
+        AF_INET => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        AF_INET6 => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        _ => return None,
+    };
+    let mut route = RouteMessage {
+        destination,
+        prefix_len: header[1],
+        table: u32::from(header[4]),
+        protocol: header[5],
+        kind: header[7],
+        gateway: None,
+        oif: None,
+        priority: None,
+        prefsrc: None,
+        nexthops: Vec::new(),
+        cloned: read_u32(header, 8)? & RTM_F_CLONED != 0,
+    };
+    for (kind, data) in attributes(&payload[RTMSG_LEN..]) {
+        match kind {
+            RTA_DST => destination = read_ip(family, data)?,
+            RTA_GATEWAY => route.gateway = read_ip(family, data),
+            RTA_OIF => route.oif = read_u32(data, 0),
+            RTA_PRIORITY => route.priority = read_u32(data, 0),
+            RTA_PREFSRC => route.prefsrc = read_ip(family, data),
+            RTA_TABLE => route.table = read_u32(data, 0)?,
+            RTA_MULTIPATH => route.nexthops = parse_nexthops(family, data),
+            _ => {}
+        }
+    }
+    route.destination = destination;
+    Some(route)
+}
+
+/// Parse the `struct rtnexthop` list of RTA_MULTIPATH. Each entry is followed by its own
+/// attributes, such as the gateway.
+fn parse_nexthops(family: u8, mut data: &[u8]) -> Vec<NextHopMessage> {
+    let mut nexthops = Vec::new();
+    while let Some(len) = read_u16(data, 0).map(usize::from) {
+        let Some(nexthop) = data.get(..len).filter(|_| len >= RTNEXTHOP_LEN) else {
+            break;
+        };
+        let Some(oif) = read_u32(nexthop, 4) else {
+            break;
+        };
+        let gateway = attributes(&nexthop[RTNEXTHOP_LEN..])
+            .find(|&(kind, _)| kind == RTA_GATEWAY)
+            .and_then(|(_, data)| read_ip(family, data));
+        nexthops.push(NextHopMessage {
+            gateway,
+            oif,
+            hops: nexthop[3],
+        });
+        data = data.get(align(len)..).unwrap_or_default();
+    }
+    nexthops
+}
+
+/// The parts of an `RTM_NEWNEIGH` or `RTM_DELNEIGH` message that describe a neighbour entry.
+pub(crate) struct NeighMessage {
+    pub(crate) index: u32,
+    pub(crate) ip: IpAddr,
+    pub(crate) lladdr: Option<Vec<u8>>,
+    /// NUD_* value.
+    pub(crate) state: u16,
+    /// NTF_* flags.
+    pub(crate) flags: u8,
+}
+
+/// Parse a neighbour entry, skipping those that are not keyed by an IP address, such as the
+/// bridge forwarding database.
+pub(crate) fn parse_neigh(payload: &[u8]) -> Option<NeighMessage> {
+    let header = payload.get(..NDMSG_LEN)?;
+    let family = header[0];
+    let mut neigh = NeighMessage {
+        index: read_u32(header, 4)?,
+        ip: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        lladdr: None,
+        state: read_u16(header, 8)?,
+        flags: header[10],
+    };
+    let mut ip = None;
+    for (kind, data) in attributes(&payload[NDMSG_LEN..]) {
+        match kind {
+            NDA_DST => ip = read_ip(family, data),
+            NDA_LLADDR if !data.is_empty() => neigh.lladdr = Some(data.to_vec()),
+            _ => {}
+        }
+    }
+    neigh.ip = ip?;
+    Some(neigh)
+}
+
+/// The parts of an `RTM_NEWRULE` message that decide which traffic a policy routing rule
+/// applies to and what happens to it.
+pub(crate) struct RuleMessage {
+    pub(crate) ipv6: bool,
+    /// Rules are evaluated in increasing order of priority.
+    pub(crate) priority: u32,
+    /// RT_TABLE_* value, from FRA_TABLE when the table id does not fit in the header.
+    pub(crate) table: u32,
+    /// FR_ACT_* value.
+    pub(crate) action: u8,
+    /// FIB_RULE_* flags.
+    pub(crate) flags: u32,
+    pub(crate) src: Option<IpAddr>,
+    pub(crate) src_len: u8,
+    pub(crate) dst: Option<IpAddr>,
+    pub(crate) dst_len: u8,
+    pub(crate) tos: u8,
+    pub(crate) iif_name: Option<String>,
+    pub(crate) oif_name: Option<String>,
+    pub(crate) fwmark: Option<u32>,
+    pub(crate) fwmask: Option<u32>,
+    /// Priority of the rule that evaluation continues at, for FR_ACT_GOTO.
+    pub(crate) goto: Option<u32>,
+    /// Routes with a prefix no longer than this are ignored when found through the rule.
+    pub(crate) suppress_prefixlen: Option<u32>,
+    /// Whether the rule also matches on something not decoded here, such as a uid range, ports
+    /// or a VRF.
+    pub(crate) other_selectors: bool,
+}
+
+impl RuleMessage {
+    /// Whether the rule applies to traffic sent from this host by a socket with no particular
+    /// source address, firewall mark or bound interface.
+    pub(crate) fn matches_unbound_traffic(&self) -> bool {
+        let selective = self.src_len != 0
+            || self.dst_len != 0
+            || self.tos != 0
+            // Locally generated traffic is looked up as if it arrived on the loopback device.
+            || self.iif_name.as_deref().is_some_and(|name| name != "lo")
+            || self.oif_name.is_some()
+            || self
+                .fwmark
+                .is_some_and(|mark| mark & self.fwmask.unwrap_or(u32::MAX) != 0)
+            || self.other_selectors;
+        selective == (self.flags & FIB_RULE_INVERT != 0)
+    }
+}
+
+pub(crate) fn parse_rule(payload: &[u8]) -> Option<RuleMessage> {
+    let header = payload.get(..FIB_RULE_HDR_LEN)?;
+    let family = header[0];
+    let ipv6 = match family {
+        AF_INET => false,
+        AF_INET6 => true,
+        _ => return None,
+    };
+    let mut rule = RuleMessage {
+        ipv6,
+        // The kernel leaves out FRA_PRIORITY for priority 0, which the local table rule uses.
+        priority: 0,
+        table: u32::from(header[4]),
+        action: header[7],
+        flags: read_u32(header, 8)?,
+        src: None,
+        src_len: header[2],
+        dst: None,
+        dst_len: header[1],
+        tos: header[3],
+        iif_name: None,
+        oif_name: None,
+        fwmark: None,
+        fwmask: None,
+        goto: None,
+        suppress_prefixlen: None,
+        other_selectors: false,
+    };
+    for (kind, data) in attributes(&payload[FIB_RULE_HDR_LEN..]) {
+        match kind {
+            FRA_SRC => rule.src = read_ip(family, data),
+            FRA_DST => rule.dst = read_ip(family, data),
+            FRA_PRIORITY => rule.priority = read_u32(data, 0)?,
+            FRA_TABLE => rule.table = read_u32(data, 0)?,
+            FRA_IIFNAME => rule.iif_name = Some(read_string(data)),
+            FRA_OIFNAME => rule.oif_name = Some(read_string(data)),
+            FRA_FWMARK => rule.fwmark = read_u32(data, 0),
+            FRA_FWMASK => rule.fwmask = read_u32(data, 0),
+            FRA_GOTO => rule.goto = read_u32(data, 0),
+            // -1 means that nothing is suppressed.
+            FRA_SUPPRESS_PREFIXLEN => {
+                rule.suppress_prefixlen = read_u32(data, 0).filter(|&len| len != u32::MAX)
+            }
+            FRA_TUN_ID | FRA_L3MDEV | FRA_UID_RANGE | FRA_IP_PROTO | FRA_SPORT_RANGE
+            | FRA_DPORT_RANGE | FRA_DSCP | FRA_FLOWLABEL => rule.other_selectors = true,
+            _ => {}
+        }
+    }
+    Some(rule)
+}
+
+/// An `RTM_NEWNDUSEROPT` message, which carries one neighbour discovery option that the kernel
+/// received but does not handle itself.
+pub(crate) struct NdUserOptMessage {
+    pub(crate) index: u32,
+    /// The ICMPv6 type of the message the option arrived in.
+    pub(crate) icmp_type: u8,
+    /// The option, starting with its type and length.
+    pub(crate) option: Vec<u8>,
+}
+
+pub(crate) fn parse_nduseropt(payload: &[u8]) -> Option<NdUserOptMessage> {
+    let header = payload.get(..NDUSEROPTMSG_LEN)?;
+    if header[0] != AF_INET6 {
+        return None;
+    }
+    let opts_len = usize::from(read_u16(header, 2)?);
+    Some(NdUserOptMessage {
+        index: read_u32(header, 4)?,
+        icmp_type: header[8],
+        option: payload
+            .get(NDUSEROPTMSG_LEN..NDUSEROPTMSG_LEN + opts_len)?
+            .to_vec(),
+    })
+}
+
+/// Why a dump did not produce a snapshot.
+enum DumpError {
+    /// The kernel refused the request with this error.
+    Rejected(Errno),
+    Failed(Error),
+}
+
+impl From<Error> for DumpError {
+    fn from(error: Error) -> Self {
+        DumpError::Failed(error)
+    }
+}
+
+impl From<DumpError> for Error {
+    fn from(error: DumpError) -> Self {
+        match error {
+            DumpError::Rejected(errno) => Error::Netlink(errno.to_string()),
+            DumpError::Failed(error) => error,
+        }
+    }
+}
+
+/// A blocking rtnetlink socket used for request/response exchanges with the kernel.
+pub(crate) struct RouteSocket {
+    fd: OwnedFd,
+    seq: u32,
+}
+
+impl RouteSocket {
+    pub(crate) fn open() -> Result<Self, Error> {
+        let fd = socket(
+            AddressFamily::Netlink,
+            SockType::Raw,
+            SockFlag::SOCK_CLOEXEC,
+            Some(SockProtocol::NetlinkRoute),
+        )
+        .map_err(|e| Error::CreateSocket(e.to_string()))?;
+        Ok(Self { fd, seq: 0 })
+    }
+
+    /// Dump links, keeping only those that parse successfully.
+    pub(crate) fn dump_links(&mut self) -> Result<Vec<LinkMessage>, Error> {
+        let items = self.dump(RTM_GETLINK, &[0; IFINFOMSG_LEN], |message| {
+            (message.kind == RTM_NEWLINK)
+                .then(|| parse_link(message.payload))
+                .flatten()
+        })?;
+        Ok(items)
+    }
+
+    /// Dump addresses of all families, keeping only those that parse successfully.
+    pub(crate) fn dump_addrs(&mut self) -> Result<Vec<AddrMessage>, Error> {
+        let items = self.dump(RTM_GETADDR, &[0; IFADDRMSG_LEN], |message| {
+            (message.kind == RTM_NEWADDR)
+                .then(|| parse_addr(message.payload))
+                .flatten()
+        })?;
+        Ok(items)
+    }
+
+    /// Dump routes of all families and tables, keeping only those that parse successfully.
+    pub(crate) fn dump_routes(&mut self) -> Result<Vec<RouteMessage>, Error> {
+        let items = self.dump(RTM_GETROUTE, &[0; RTMSG_LEN], |message| {
+            (message.kind == RTM_NEWROUTE)
+                .then(|| parse_route(message.payload))
+                .flatten()
+        })?;
+        Ok(items)
+    }
+
+    /// Dump the neighbour entries of all IP families, keeping only those that parse successfully.
+    pub(crate) fn dump_neighbours(&mut self) -> Result<Vec<NeighMessage>, Error> {
+        let items = self.dump(RTM_GETNEIGH, &[0; NDMSG_LEN], |message| {
+            (message.kind == RTM_NEWNEIGH)
+                .then(|| parse_neigh(message.payload))
+                .flatten()
+        })?;
+        Ok(items)
+    }
+
+    /// Ask the kernel which route it would use to send traffic to `destination`.
+    ///
+    /// Returns `None` if the kernel reports the destination as unreachable.
+    pub(crate) fn get_route(&mut self, destination: IpAddr) -> Result<Option<RouteMessage>, Error> {
+        let (family, octets) = match destination {
+            IpAddr::V4(v4) => (AF_INET, v4.octets().to_vec()),
+            IpAddr::V6(v6) => (AF_INET6, v6.octets().to_vec()),
+        };
+        let mut request = vec![0u8; RTMSG_LEN];
+        request[0] = family;
+        request[1] = (octets.len() * 8) as u8;
+        request.extend(encode_attr(RTA_DST, &octets));
+        let seq = self.send(RTM_GETROUTE, NLM_F_REQUEST, &request)?;
+
+        let mut buf = vec![0u8; RECV_BUFFER_LEN];
+        loop {
+            let len = match recv(self.fd.as_raw_fd(), &mut buf, MsgFlags::empty()) {
+                Ok(len) => len,
+                Err(Errno::EINTR) => continue,
+                Err(e) => return Err(Error::Netlink(e.to_string())),
+            };
+            if len == 0 {
+                return Err(Error::Netlink("socket closed during request".to_string()));
+            }
+            for message in messages(&buf[..len]) {
+                if message.seq != seq {
+                    continue;
+                }
+                match message.kind {
+                    RTM_NEWROUTE => {
+                        return parse_route(message.payload)
+                            .map(Some)
+                            .ok_or_else(|| Error::Netlink("malformed route in reply".to_string()))
+                    }
+                    NLMSG_ERROR => match Errno::from_raw(-error_code(message.payload)) {
+                        Errno::ENETUNREACH | Errno::EHOSTUNREACH => return Ok(None),
+                        e => return Err(Error::Netlink(e.to_string())),
+                    },
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Dump policy routing rules of all families, keeping only those that parse successfully.
+    ///
+    /// Returns `None` if the kernel was built without policy routing.
+    pub(crate) fn dump_rules(&mut self) -> Result<Option<Vec<RuleMessage>>, Error> {
+        let rules = self.dump(RTM_GETRULE, &[0; FIB_RULE_HDR_LEN], |message| {
+            (message.kind == RTM_NEWRULE)
+                .then(|| parse_rule(message.payload))
+                .flatten()
+        });
+        match rules {
+            Ok(rules) => Ok(Some(rules)),
+            Err(DumpError::Rejected(Errno::EOPNOTSUPP | Errno::EAFNOSUPPORT)) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Issue a dump request and collect the parsed replies.
+    ///
+    /// If the kernel reports that the dump was interrupted by a concurrent change the partial
+    /// results are discarded and the dump is requested again, so the returned set is always a
+    /// consistent snapshot.
+    fn dump<T>(
+        &mut self,
+        kind: u16,
+        header: &[u8],
+        mut parse: impl FnMut(&Message<'_>) -> Option<T>,
+    ) -> Result<Vec<T>, DumpError> {
+        for _ in 0..DUMP_ATTEMPTS {
+            if let Some(items) = self.dump_once(kind, header, &mut parse)? {
+                return Ok(items);
+            }
+        }
+        Err(Error::Netlink("dump was repeatedly interrupted".to_string()).into())
+    }
+
+    /// Send a request and return the sequence number that replies will carry.
+    fn send(&mut self, kind: u16, flags: u16, header: &[u8]) -> Result<u32, Error> {
+        self.seq = self.seq.wrapping_add(1);
+        let request = encode_request(kind, flags, self.seq, header);
+        sendto(
+            self.fd.as_raw_fd(),
+            &request,
+            &NetlinkAddr::new(0, 0),
+            MsgFlags::empty(),
+        )
+        .map_err(|e| Error::Netlink(e.to_string()))?;
+        Ok(self.seq)
+    }
+
+    fn dump_once<T>(
+        &mut self,
+        kind: u16,
+        header: &[u8],
+        parse: &mut impl FnMut(&Message<'_>) -> Option<T>,
+    ) -> Result<Option<Vec<T>>, DumpError> {
+        let seq = self.send(kind, NLM_F_REQUEST | NLM_F_DUMP, header)?;
+
+        let mut items = Vec::new();
+        let mut interrupted = false;
+        let mut buf = vec![0u8; RECV_BUFFER_LEN];
+        loop {
+            let len = match recv(self.fd.as_raw_fd(), &mut buf, MsgFlags::empty()) {
+                Ok(len) => len,
+                Err(Errno::EINTR) => continue,
+                Err(e) => return Err(Error::Netlink(e.to_string()).into()),
+            };
+            if len == 0 {
+                return Err(Error::Netlink("socket closed during dump".to_string()).into());
+            }
+            for message in messages(&buf[..len]) {
+                // Replies to an earlier, abandoned request may still be queued.
+                if message.seq != seq {
+                    continue;
+                }
+                if message.flags & NLM_F_DUMP_INTR != 0 {
+                    interrupted = true;
+                }
+                match message.kind {
+                    NLMSG_DONE => return Ok((!interrupted).then_some(items)),
+                    NLMSG_ERROR => {
+                        let code = error_code(message.payload);
+                        if code != 0 {
+                            return Err(DumpError::Rejected(Errno::from_raw(-code)));
+                        }
+                    }
+                    _ => items.extend(parse(&message)),
+                }
+            }
+        }
+    }
+}
+
+fn encode_request(kind: u16, flags: u16, seq: u32, header: &[u8]) -> Vec<u8> {
+    let len = NLMSG_HDRLEN + header.len();
+    let mut request = Vec::with_capacity(align(len));
+    request.extend_from_slice(&(len as u32).to_ne_bytes());
+    request.extend_from_slice(&kind.to_ne_bytes());
+    request.extend_from_slice(&flags.to_ne_bytes());
+    request.extend_from_slice(&seq.to_ne_bytes());
+    request.extend_from_slice(&0u32.to_ne_bytes());
+    request.extend_from_slice(header);
+    request.resize(align(len), 0);
+    request
+}
+
+fn encode_attr(kind: u16, data: &[u8]) -> Vec<u8> {
+    let len = 4 + data.len();
+    let mut out = Vec::with_capacity(align(len));
+    out.extend_from_slice(&(len as u16).to_ne_bytes());
+    out.extend_from_slice(&kind.to_ne_bytes());
+    out.extend_from_slice(data);
+    out.resize(align(len), 0);
+    out
+}
+
+/// The negated errno at the start of an `NLMSG_ERROR` payload, where 0 is an acknowledgement.
+fn error_code(payload: &[u8]) -> i32 {
+    payload
+        .get(..4)
+        .map(|b| i32::from_ne_bytes(b.try_into().unwrap()))
+        .unwrap_or(0)
+}
+
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+fn read_u16(buf: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_ne_bytes(
+        buf.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(buf: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_ne_bytes(
+        buf.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_u64(buf: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_ne_bytes(
+        buf.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+fn read_string(data: &[u8]) -> String {
+    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..end]).into_owned()
+}
+
+fn read_ip(family: u8, data: &[u8]) -> Option<IpAddr> {
+    match family {
+        AF_INET => {
+            let octets: [u8; 4] = data.try_into().ok()?;
+            Some(IpAddr::V4(Ipv4Addr::from(octets)))
+        }
+        AF_INET6 => {
+            let octets: [u8; 16] = data.try_into().ok()?;
+            Some(IpAddr::V6(Ipv6Addr::from(octets)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn attr(kind: u16, data: &[u8]) -> Vec<u8> {
+        encode_attr(kind, data)
+    }
+
+    pub(crate) fn link_payload(index: u32, flags: u32, name: &str, hw_addr: &[u8]) -> Vec<u8> {
+        let mut payload = vec![0u8; IFINFOMSG_LEN];
+        payload[4..8].copy_from_slice(&index.to_ne_bytes());
+        payload[8..12].copy_from_slice(&flags.to_ne_bytes());
+        let mut name = name.as_bytes().to_vec();
+        name.push(0);
+        payload.extend(attr(IFLA_IFNAME, &name));
+        if !hw_addr.is_empty() {
+            payload.extend(attr(IFLA_ADDRESS, hw_addr));
+        }
+        payload
+    }
+
+    pub(crate) fn addr_payload(index: u32, ip: IpAddr, prefix_len: u8) -> Vec<u8> {
+        let (family, octets) = match ip {
+            IpAddr::V4(v4) => (AF_INET, v4.octets().to_vec()),
+            IpAddr::V6(v6) => (AF_INET6, v6.octets().to_vec()),
+        };
+        let mut payload = vec![family, prefix_len, 0, 0];
+        payload.extend_from_slice(&index.to_ne_bytes());
+        payload.extend(attr(IFA_ADDRESS, &octets));
+        payload
+    }
+
+    pub(crate) fn route_payload(destination: IpAddr, prefix_len: u8, table: u8) -> Vec<u8> {
+        let (family, octets) = match destination {
+            IpAddr::V4(v4) => (AF_INET, v4.octets().to_vec()),
+            IpAddr::V6(v6) => (AF_INET6, v6.octets().to_vec()),
+        };
+        // Statically configured unicast route
+        let mut payload = vec![family, prefix_len, 0, 0, table, 4, 0, 1];
+        payload.extend_from_slice(&0u32.to_ne_bytes());
+        if prefix_len != 0 {
+            payload.extend(attr(RTA_DST, &octets));
+        }
+        payload
+    }
+
+    pub(crate) fn addr_local(ip: IpAddr) -> Vec<u8> {
+        match ip {
+            IpAddr::V4(v4) => attr(IFA_LOCAL, &v4.octets()),
+            IpAddr::V6(v6) => attr(IFA_LOCAL, &v6.octets()),
+        }
+    }
+
+    pub(crate) fn addr_flags(flags: u32) -> Vec<u8> {
+        attr(IFA_FLAGS, &flags.to_ne_bytes())
+    }
+
+    pub(crate) fn addr_cacheinfo(preferred: u32, valid: u32) -> Vec<u8> {
+        let mut cacheinfo = Vec::new();
+        for value in [preferred, valid, 0, 0] {
+            cacheinfo.extend_from_slice(&value.to_ne_bytes());
+        }
+        attr(IFA_CACHEINFO, &cacheinfo)
+    }
+
+    pub(crate) fn message(kind: u16, payload: &[u8]) -> Vec<u8> {
+        encode_request(kind, 0, 0, payload)
+    }
+
+    #[test]
+    fn link_message_round_trips() {
+        let mut payload = link_payload(3, IFF_UP, "eth0", &[2, 0, 0, 0, 0, 1]);
+        payload.extend(attr(IFLA_MTU, &1500u32.to_ne_bytes()));
+        payload.extend(attr(IFLA_LINKINFO, &attr(IFLA_INFO_KIND, b"veth\0")));
+        payload.extend(attr(IFLA_PERM_ADDRESS, &[0, 0x1b, 0x21, 0, 0, 1]));
+
+        let link = parse_link(&payload).unwrap();
+
+        assert_eq!(link.index, 3);
+        assert_eq!(link.flags, IFF_UP);
+        assert_eq!(link.name.as_deref(), Some("eth0"));
+        assert_eq!(link.hw_addr, Some(vec![2, 0, 0, 0, 0, 1]));
+        assert_eq!(link.perm_hw_addr, Some(vec![0, 0x1b, 0x21, 0, 0, 1]));
+        assert_eq!(link.mtu, Some(1500));
+        assert_eq!(link.kind.as_deref(), Some("veth"));
+    }
+
+    #[test]
+    fn master_and_lower_device_are_decoded() {
+        let mut payload = link_payload(4, IFF_UP, "veth0", &[]);
+        payload.extend(attr(IFLA_MASTER, &7u32.to_ne_bytes()));
+        payload.extend(attr(IFLA_LINK, &5u32.to_ne_bytes()));
+        payload.extend(attr(IFLA_LINK_NETNSID, &1i32.to_ne_bytes()));
+
+        let link = parse_link(&payload).unwrap();
+
+        assert_eq!(link.master, Some(7));
+        assert_eq!(link.link, Some(5));
+        assert_eq!(link.link_netnsid, Some(1));
+    }
+
+    #[test]
+    fn stats64_counters_are_decoded() {
+        let counters: Vec<u8> = (1..=24u64).flat_map(|n| n.to_ne_bytes()).collect();
+        let mut payload = link_payload(1, IFF_UP, "eth0", &[]);
+        payload.extend(attr(IFLA_STATS64, &counters));
+
+        let stats = parse_link(&payload).unwrap().stats.unwrap();
+
+        assert_eq!(stats.rx_packets, 1);
+        assert_eq!(stats.tx_packets, 2);
+        assert_eq!(stats.rx_bytes, 3);
+        assert_eq!(stats.tx_bytes, 4);
+        assert_eq!(stats.tx_dropped, 8);
+    }
+
+    #[test]
+    fn alt_names_and_alias_are_decoded() {
+        let mut props = attr(IFLA_ALT_IFNAME, b"enp0s31f6\0");
+        props.extend(attr(IFLA_ALT_IFNAME, b"uplink\0"));
+        let mut payload = link_payload(2, IFF_UP, "eth0", &[]);
+        payload.extend(attr(IFLA_PROP_LIST, &props));
+        payload.extend(attr(IFLA_IFALIAS, b"rack 4 port 2\0"));
+
+        let link = parse_link(&payload).unwrap();
+
+        assert_eq!(link.alt_names, vec!["enp0s31f6", "uplink"]);
+        assert_eq!(link.alias.as_deref(), Some("rack 4 port 2"));
+
+        let mut payload = link_payload(2, IFF_UP, "eth0", &[]);
+        payload.extend(attr(IFLA_IFALIAS, b""));
+        assert_eq!(parse_link(&payload).unwrap().alias, None);
+    }
+
+    #[test]
+    fn default_route_is_decoded() {
+        let mut payload = route_payload(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0, 254);
+        payload.extend(attr(RTA_GATEWAY, &[192, 0, 2, 1]));
+        payload.extend(attr(RTA_OIF, &2u32.to_ne_bytes()));
+        payload.extend(attr(RTA_PRIORITY, &100u32.to_ne_bytes()));
+        payload.extend(attr(RTA_PREFSRC, &[192, 0, 2, 10]));
+
+        let route = parse_route(&payload).unwrap();
+
+        assert_eq!(route.destination, IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        assert_eq!(route.prefix_len, 0);
+        assert_eq!(route.table, 254);
+        assert_eq!(route.protocol, 4);
+        assert_eq!(route.kind, 1);
+        assert_eq!(route.gateway, Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))));
+        assert_eq!(route.oif, Some(2));
+        assert_eq!(route.priority, Some(100));
+        assert_eq!(
+            route.prefsrc,
+            Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 10)))
+        );
+    }
+
+    #[test]
+    fn multipath_route_is_decoded() {
+        let mut multipath = Vec::new();
+        for (oif, hops, gateway) in [(2u32, 0u8, [192, 0, 2, 1]), (3, 4, [198, 51, 100, 1])] {
+            let gateway = attr(RTA_GATEWAY, &gateway);
+            let len = (RTNEXTHOP_LEN + gateway.len()) as u16;
+            multipath.extend_from_slice(&len.to_ne_bytes());
+            multipath.extend_from_slice(&[0, hops]);
+            multipath.extend_from_slice(&oif.to_ne_bytes());
+            multipath.extend(gateway);
+        }
+        let mut payload = route_payload(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0, 254);
+        payload.extend(attr(RTA_MULTIPATH, &multipath));
+
+        let route = parse_route(&payload).unwrap();
+
+        assert_eq!(route.gateway, None);
+        assert_eq!(route.oif, None);
+        let nexthops: Vec<_> = route
+            .nexthops
+            .iter()
+            .map(|nexthop| (nexthop.gateway, nexthop.oif, nexthop.hops))
+            .collect();
+        assert_eq!(
+            nexthops,
+            vec![
+                (Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))), 2, 0),
+                (Some(IpAddr::V4(Ipv4Addr::new(198, 51, 100, 1))), 3, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn route_table_attribute_overrides_header() {
+        let destination = IpAddr::V6("2001:db8::".parse().unwrap());
+        let mut payload = route_payload(destination, 32, 252);
+        payload.extend(attr(RTA_TABLE, &1000u32.to_ne_bytes()));
+
+        let route = parse_route(&payload).unwrap();
+
+        assert_eq!(route.destination, destination);
+        assert_eq!(route.prefix_len, 32);
+        assert_eq!(route.table, 1000);
+    }
+
+    fn neigh_payload(family: u8, index: u32, state: u16, flags: u8) -> Vec<u8> {
+        let mut payload = vec![family, 0, 0, 0];
+        payload.extend_from_slice(&index.to_ne_bytes());
+        payload.extend_from_slice(&state.to_ne_bytes());
+        payload.extend_from_slice(&[flags, 1]);
+        payload
+    }
+
+    #[test]
+    fn neighbour_is_decoded() {
+        let mut payload = neigh_payload(AF_INET6, 3, 0x02, NTF_ROUTER);
+        payload.extend(attr(NDA_DST, &Ipv6Addr::LOCALHOST.octets()));
+        payload.extend(attr(NDA_LLADDR, &[2, 0, 0, 0, 0, 1]));
+
+        let neigh = parse_neigh(&payload).unwrap();
+
+        assert_eq!(neigh.index, 3);
+        assert_eq!(neigh.ip, IpAddr::V6(Ipv6Addr::LOCALHOST));
+        assert_eq!(neigh.lladdr, Some(vec![2, 0, 0, 0, 0, 1]));
+        assert_eq!(neigh.state, 0x02);
+        assert_eq!(neigh.flags, NTF_ROUTER);
+
+        // An incomplete entry has no link-layer address yet, and bridge entries have no IP.
+        let mut payload = neigh_payload(AF_INET, 3, 0x01, 0);
+        payload.extend(attr(NDA_DST, &[192, 0, 2, 1]));
+        assert_eq!(parse_neigh(&payload).unwrap().lladdr, None);
+        let mut payload = neigh_payload(7, 3, 0x80, 0);
+        payload.extend(attr(NDA_LLADDR, &[2, 0, 0, 0, 0, 1]));
+        assert!(parse_neigh(&payload).is_none());
+    }
+
+    fn rule_payload(family: u8, action: u8, table: u8, flags: u32) -> Vec<u8> {
+        let mut payload = vec![family, 0, 0, 0, table, 0, 0, action];
+        payload.extend_from_slice(&flags.to_ne_bytes());
+        payload
+    }
+
+    #[test]
+    fn rule_is_decoded() {
+        let mut payload = rule_payload(AF_INET6, FR_ACT_TO_TBL, 252, 0);
+        payload.extend(attr(FRA_PRIORITY, &32764u32.to_ne_bytes()));
+        payload.extend(attr(FRA_TABLE, &51820u32.to_ne_bytes()));
+        payload.extend(attr(FRA_SUPPRESS_PREFIXLEN, &0u32.to_ne_bytes()));
+
+        let rule = parse_rule(&payload).unwrap();
+
+        assert!(rule.ipv6);
+        assert_eq!(rule.priority, 32764);
+        assert_eq!(rule.table, 51820);
+        assert_eq!(rule.action, FR_ACT_TO_TBL);
+        assert_eq!(rule.suppress_prefixlen, Some(0));
+        assert!(rule.matches_unbound_traffic());
+
+        let mut payload = rule_payload(AF_INET, FR_ACT_TO_TBL, 254, 0);
+        payload[2] = 24;
+        payload.extend(attr(FRA_SRC, &[192, 0, 2, 0]));
+        payload.extend(attr(FRA_SUPPRESS_PREFIXLEN, &u32::MAX.to_ne_bytes()));
+        let rule = parse_rule(&payload).unwrap();
+        assert_eq!(rule.priority, 0);
+        assert_eq!(rule.src, Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 0))));
+        assert_eq!(rule.src_len, 24);
+        assert_eq!(rule.dst, None);
+        assert_eq!(rule.suppress_prefixlen, None);
+        assert!(!rule.matches_unbound_traffic());
+    }
+
+    #[test]
+    fn rule_selectors_limit_which_traffic_matches() {
+        let matches = |attrs: &[Vec<u8>], flags: u32| {
+            let mut payload = rule_payload(AF_INET, FR_ACT_TO_TBL, 100, flags);
+            for attr in attrs {
+                payload.extend(attr);
+            }
+            parse_rule(&payload).unwrap().matches_unbound_traffic()
+        };
+        let fwmark = attr(FRA_FWMARK, &0xca6cu32.to_ne_bytes());
+
+        assert!(!matches(std::slice::from_ref(&fwmark), 0));
+        assert!(matches(std::slice::from_ref(&fwmark), FIB_RULE_INVERT));
+        assert!(!matches(&[], FIB_RULE_INVERT));
+        assert!(matches(&[attr(FRA_IIFNAME, b"lo\0")], 0));
+        assert!(!matches(&[attr(FRA_IIFNAME, b"eth0\0")], 0));
+        assert!(!matches(&[attr(FRA_UID_RANGE, &[0; 8])], 0));
+        assert!(matches(
+            &[
+                attr(FRA_FWMARK, &0x100u32.to_ne_bytes()),
+                attr(FRA_FWMASK, &0xffu32.to_ne_bytes())
+            ],
+            0
+        ));
+    }
+
+    #[test]
+    fn local_address_is_preferred_over_peer() {
+        let mut payload = vec![AF_INET, 32, 0, 0];
+        payload.extend_from_slice(&7u32.to_ne_bytes());
+        payload.extend(attr(IFA_ADDRESS, &[10, 0, 0, 2]));
+        payload.extend(attr(IFA_LOCAL, &[10, 0, 0, 1]));
+
+        let addr = parse_addr(&payload).unwrap();
+
+        assert_eq!(addr.index, 7);
+        assert_eq!(addr.ip, IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+        assert_eq!(addr.peer, Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2))));
+        assert_eq!(addr.prefix_len, 32);
+    }
+
+    #[test]
+    fn scope_and_label_are_decoded() {
+        let mut payload = addr_payload(2, IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)), 24);
+        payload[3] = 253;
+        payload.extend(attr(IFA_LABEL, b"eth0:1\0"));
+
+        let addr = parse_addr(&payload).unwrap();
+
+        assert_eq!(addr.scope, 253);
+        assert_eq!(addr.label.as_deref(), Some("eth0:1"));
+    }
+
+    #[test]
+    fn extended_address_flags_replace_header_flags() {
+        let mut payload = addr_payload(1, IpAddr::V6(Ipv6Addr::LOCALHOST), 128);
+        payload[2] = IFA_F_PERMANENT as u8;
+        assert_eq!(parse_addr(&payload).unwrap().flags, IFA_F_PERMANENT);
+
+        payload.extend(addr_flags(IFA_F_PERMANENT | 0x800));
+        assert_eq!(parse_addr(&payload).unwrap().flags, IFA_F_PERMANENT | 0x800);
+    }
+
+    #[test]
+    fn cacheinfo_lifetimes_are_decoded() {
+        let mut payload = addr_payload(1, IpAddr::V6(Ipv6Addr::LOCALHOST), 128);
+        assert_eq!(parse_addr(&payload).unwrap().lifetimes, None);
+
+        payload.extend(addr_cacheinfo(1800, 3600));
+        assert_eq!(parse_addr(&payload).unwrap().lifetimes, Some((1800, 3600)));
+    }
+
+    #[test]
+    fn multiple_messages_are_split_and_truncation_stops_iteration() {
+        let mut buf = message(RTM_NEWLINK, &link_payload(1, 0, "lo", &[]));
+        buf.extend(message(
+            RTM_NEWADDR,
+            &addr_payload(1, IpAddr::V6(Ipv6Addr::LOCALHOST), 128),
+        ));
+        let complete_len = buf.len();
+        buf.extend(message(
+            RTM_NEWADDR,
+            &addr_payload(1, Ipv4Addr::LOCALHOST.into(), 8),
+        ));
+
+        let kinds: Vec<u16> = messages(&buf).map(|m| m.kind).collect();
+        assert_eq!(kinds, vec![RTM_NEWLINK, RTM_NEWADDR, RTM_NEWADDR]);
+
+        let kinds: Vec<u16> = messages(&buf[..complete_len + 10])
+            .map(|m| m.kind)
+            .collect();
+        assert_eq!(kinds, vec![RTM_NEWLINK, RTM_NEWADDR]);
+    }
+
+    #[test]
+    fn nduseropt_is_decoded() {
+        let option = [25, 3, 0, 0, 0, 0, 0x0e, 0x10];
+        let mut payload = vec![AF_INET6, 0];
+        payload.extend_from_slice(&(option.len() as u16).to_ne_bytes());
+        payload.extend_from_slice(&4u32.to_ne_bytes());
+        payload.extend_from_slice(&[ND_ROUTER_ADVERT, 0, 0, 0, 0, 0, 0, 0]);
+        payload.extend_from_slice(&option);
+        payload.extend(attr(1, &[0xfe, 0x80]));
+
+        let message = parse_nduseropt(&payload).unwrap();
+
+        assert_eq!(message.index, 4);
+        assert_eq!(message.icmp_type, ND_ROUTER_ADVERT);
+        assert_eq!(message.option, option);
+
+        // The option length runs past the end of the message.
+        payload.truncate(NDUSEROPTMSG_LEN + 4);
+        assert!(parse_nduseropt(&payload).is_none());
+    }
+
+    #[test]
+    fn malformed_attribute_is_ignored() {
+        let mut payload = link_payload(1, 0, "lo", &[]);
+        // Attribute claims to be longer than the remaining buffer.
+        payload.extend_from_slice(&64u16.to_ne_bytes());
+        payload.extend_from_slice(&IFLA_ADDRESS.to_ne_bytes());
+
+        let link = parse_link(&payload).unwrap();
+
+        assert_eq!(link.name.as_deref(), Some("lo"));
+        assert_eq!(link.hw_addr, None);
+    }
+}
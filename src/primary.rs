@@ -0,0 +1,586 @@
+#[cfg(any(target_os = "linux", test))]
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+#[cfg(any(target_os = "linux", test))]
+use crate::{Interface, IpRecord, Route, RouteType};
+
+/// The path that traffic to the internet takes for one address family, as returned by
+/// `primary_paths`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PrimaryPath {
+    /// Index of the interface that the default route leaves through.
+    pub ifindex: u32,
+    /// Name of that interface.
+    pub interface_name: String,
+    /// The router that traffic is forwarded to. `None` if the default route needs no next hop,
+    /// e.g. on a point-to-point tunnel.
+    pub gateway: Option<IpAddr>,
+    /// The source address that new connections will use. This is the default route's preferred
+    /// source if it has one, otherwise the address the kernel would choose from the interface.
+    pub source: Option<IpAddr>,
+}
+
+/// The primary path of each address family.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrimaryPaths {
+    /// `None` if there is no usable IPv4 default route.
+    pub ipv4: Option<PrimaryPath>,
+    /// `None` if there is no usable IPv6 default route.
+    pub ipv6: Option<PrimaryPath>,
+}
+
+/// Information delivered when the primary path of either address family changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrimaryPathUpdate {
+    /// Whether this update represents the initial primary paths.
+    pub is_initial: bool,
+    /// The current primary paths.
+    pub paths: PrimaryPaths,
+    /// Whether `paths.ipv4` differs from the preceding update. For an initial update, whether
+    /// there is an IPv4 path at all.
+    pub ipv4_changed: bool,
+    /// Whether `paths.ipv6` differs from the preceding update. For an initial update, whether
+    /// there is an IPv6 path at all.
+    pub ipv6_changed: bool,
+}
+
+/// A policy routing rule, reduced to what decides where ordinary traffic from this host goes.
+#[cfg(any(target_os = "linux", test))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PolicyRule {
+    pub(crate) ipv6: bool,
+    pub(crate) priority: u32,
+    /// Whether the rule applies to traffic with no particular source, mark or interface.
+    pub(crate) matches_any: bool,
+    pub(crate) action: PolicyAction,
+    /// Routes with a prefix no longer than this are ignored when found through this rule.
+    pub(crate) suppress_prefixlen: Option<u32>,
+}
+
+#[cfg(any(target_os = "linux", test))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PolicyAction {
+    /// Look up the given table.
+    Table(u32),
+    /// Continue with the first rule whose priority is at least the given one.
+    Goto(u32),
+    /// Do nothing and continue with the next rule.
+    Nop,
+    /// Drop the traffic.
+    Reject,
+}
+
+#[cfg(any(target_os = "linux", test))]
+impl PolicyRule {
+    fn lookup(ipv6: bool, priority: u32, table: u32) -> Self {
+        Self {
+            ipv6,
+            priority,
+            matches_any: true,
+            action: PolicyAction::Table(table),
+            suppress_prefixlen: None,
+        }
+    }
+
+    /// The rules the kernel starts with, used when no rules could be read.
+    fn defaults(ipv6: bool) -> Vec<Self> {
+        vec![
+            Self::lookup(ipv6, 0, Route::LOCAL_TABLE),
+            Self::lookup(ipv6, 32766, Route::MAIN_TABLE),
+            Self::lookup(ipv6, 32767, DEFAULT_TABLE),
+        ]
+    }
+}
+
+#[cfg(any(target_os = "linux", test))]
+const DEFAULT_TABLE: u32 = 253;
+
+#[cfg(any(target_os = "linux", test))]
+enum TableLookup<'a> {
+    /// The default route, the interface it leaves through and the gateway on that interface.
+    Found(&'a Route, &'a Interface, Option<IpAddr>),
+    /// The table has no usable default route, so evaluation continues with the next rule.
+    Miss,
+    /// The table's default route discards traffic.
+    Reject,
+}
+
+/// Work out the primary path of each family the way the kernel would route traffic to an
+/// arbitrary internet address, following the policy rules in order and choosing the default
+/// route with the lowest metric within each table.
+///
+/// Default routes through an interface that is not running are skipped. A multipath default
+/// route spreads connections over its next hops, so the first one whose interface is running
+/// stands for it.
+#[cfg(any(target_os = "linux", test))]
+pub(crate) fn select_primary_paths(
+    routes: &[Route],
+    rules: &[PolicyRule],
+    interfaces: &HashMap<u32, Interface>,
+) -> PrimaryPaths {
+    PrimaryPaths {
+        ipv4: select_primary_path(false, routes, rules, interfaces),
+        ipv6: select_primary_path(true, routes, rules, interfaces),
+    }
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn select_primary_path(
+    ipv6: bool,
+    routes: &[Route],
+    rules: &[PolicyRule],
+    interfaces: &HashMap<u32, Interface>,
+) -> Option<PrimaryPath> {
+    let mut rules: Vec<PolicyRule> = rules
+        .iter()
+        .filter(|rule| rule.ipv6 == ipv6)
+        .cloned()
+        .collect();
+    if rules.is_empty() {
+        rules = PolicyRule::defaults(ipv6);
+    }
+    rules.sort_by_key(|rule| rule.priority);
+
+    let mut goto: Option<u32> = None;
+    for rule in &rules {
+        if let Some(target) = goto {
+            if rule.priority < target {
+                continue;
+            }
+            goto = None;
+        }
+        if !rule.matches_any {
+            continue;
+        }
+        let table = match rule.action {
+            PolicyAction::Table(table) => table,
+            PolicyAction::Goto(target) => {
+                goto = Some(target);
+                continue;
+            }
+            PolicyAction::Nop => continue,
+            PolicyAction::Reject => return None,
+        };
+        match lookup_default_route(ipv6, table, routes, interfaces) {
+            TableLookup::Found(route, interface, gateway) => {
+                if rule
+                    .suppress_prefixlen
+                    .is_some_and(|len| u32::from(route.prefix_len) <= len)
+                {
+                    continue;
+                }
+                return Some(PrimaryPath {
+                    ifindex: interface.index,
+                    interface_name: interface.name.clone(),
+                    gateway,
+                    source: route
+                        .source
+                        .or_else(|| choose_source(interface, ipv6, gateway)),
+                });
+            }
+            TableLookup::Miss => continue,
+            TableLookup::Reject => return None,
+        }
+    }
+    None
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn lookup_default_route<'a>(
+    ipv6: bool,
+    table: u32,
+    routes: &'a [Route],
+    interfaces: &'a HashMap<u32, Interface>,
+) -> TableLookup<'a> {
+    let mut candidates: Vec<&Route> = routes
+        .iter()
+        .filter(|route| {
+            route.table == table && route.is_default() && route.destination.is_ipv6() == ipv6
+        })
+        .collect();
+    // A route without a metric has the lowest possible one.
+    candidates.sort_by_key(|route| route.metric.unwrap_or(0));
+    for route in candidates {
+        match route.kind {
+            RouteType::Unicast => {
+                let running = |ifindex: u32| {
+                    interfaces
+                        .get(&ifindex)
+                        .filter(|interface| interface.flags.running)
+                };
+                let found = if route.nexthops.is_empty() {
+                    route
+                        .ifindex
+                        .and_then(running)
+                        .map(|interface| (interface, route.gateway))
+                } else {
+                    route.nexthops.iter().find_map(|nexthop| {
+                        running(nexthop.ifindex).map(|interface| (interface, nexthop.gateway))
+                    })
+                };
+                if let Some((interface, gateway)) = found {
+                    return TableLookup::Found(route, interface, gateway);
+                }
+            }
+            RouteType::Throw => return TableLookup::Miss,
+            RouteType::Blackhole | RouteType::Unreachable | RouteType::Prohibit => {
+                return TableLookup::Reject
+            }
+            _ => {}
+        }
+    }
+    TableLookup::Miss
+}
+
+/// Pick the address the kernel would use as the source for a route without a preferred source:
+/// a usable global address, preferably in the same subnet as the gateway.
+#[cfg(any(target_os = "linux", test))]
+fn choose_source(interface: &Interface, ipv6: bool, gateway: Option<IpAddr>) -> Option<IpAddr> {
+    let usable: Vec<&IpRecord> = interface
+        .ips
+        .iter()
+        .filter(|record| record.ip.is_ipv6() == ipv6)
+        .filter(|record| {
+            interface.addr_info.get(*record).is_none_or(|info| {
+                !info.flags.secondary
+                    && !info.flags.tentative
+                    && !info.flags.dad_failed
+                    && !info.flags.deprecated
+                    && info
+                        .scope
+                        .is_none_or(|scope| scope == crate::AddrScope::Global)
+            })
+        })
+        .collect();
+    usable
+        .iter()
+        .find(|record| gateway.is_some_and(|gateway| in_prefix(record, gateway)))
+        .or_else(|| usable.first())
+        .map(|record| record.ip)
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn in_prefix(record: &IpRecord, ip: IpAddr) -> bool {
+    // Both addresses are aligned to the top of a u128 so that one mask works for either family.
+    let (network, ip, bits) = match (record.ip, ip) {
+        (IpAddr::V4(network), IpAddr::V4(ip)) => (
+            u128::from(u32::from(network)) << 96,
+            u128::from(u32::from(ip)) << 96,
+            32,
+        ),
+        (IpAddr::V6(network), IpAddr::V6(ip)) => (u128::from(network), u128::from(ip), 128),
+        _ => return false,
+    };
+    let prefix_len = u32::from(record.prefix_len).min(bits);
+    let mask = u128::MAX.checked_shl(128 - prefix_len).unwrap_or(0);
+    network & mask == ip & mask
+}
+
+/// Turns successive primary path snapshots into updates, skipping those where neither path
+/// changed.
+#[cfg(any(target_os = "linux", test))]
+#[derive(Default)]
+pub(crate) struct PrimaryPathCursor {
+    prev: Option<PrimaryPaths>,
+}
+
+#[cfg(any(target_os = "linux", test))]
+impl PrimaryPathCursor {
+    pub(crate) fn advance(&mut self, paths: PrimaryPaths) -> Option<PrimaryPathUpdate> {
+        let update = match &self.prev {
+            None => PrimaryPathUpdate {
+                is_initial: true,
+                ipv4_changed: paths.ipv4.is_some(),
+                ipv6_changed: paths.ipv6.is_some(),
+                paths: paths.clone(),
+            },
+            Some(prev) if *prev == paths => return None,
+            Some(prev) => PrimaryPathUpdate {
+                is_initial: false,
+                ipv4_changed: prev.ipv4 != paths.ipv4,
+                ipv6_changed: prev.ipv6 != paths.ipv6,
+                paths: paths.clone(),
+            },
+        };
+        self.prev = Some(paths);
+        Some(update)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AddrFlags, AddrInfo, LinkFlags, NextHop, RouteProtocol};
+    use std::net::Ipv4Addr;
+
+    fn interface(index: u32, name: &str, ips: &[(Ipv4Addr, u8)]) -> Interface {
+        Interface {
+            index,
+            name: name.to_string(),
+            ips: ips
+                .iter()
+                .map(|&(ip, prefix_len)| IpRecord {
+                    ip: IpAddr::V4(ip),
+                    prefix_len,
+                })
+                .collect(),
+            flags: LinkFlags {
+                up: true,
+                running: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn interfaces() -> HashMap<u32, Interface> {
+        [
+            interface(2, "eth0", &[(Ipv4Addr::new(192, 0, 2, 10), 24)]),
+            interface(3, "wlan0", &[(Ipv4Addr::new(198, 51, 100, 10), 24)]),
+            interface(4, "wg0", &[(Ipv4Addr::new(10, 8, 0, 2), 32)]),
+        ]
+        .into_iter()
+        .map(|interface| (interface.index, interface))
+        .collect()
+    }
+
+    fn default_route(ifindex: u32, gateway: Option<Ipv4Addr>, metric: u32, table: u32) -> Route {
+        Route {
+            destination: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            prefix_len: 0,
+            gateway: gateway.map(IpAddr::V4),
+            ifindex: Some(ifindex),
+            nexthops: Vec::new(),
+            source: None,
+            metric: Some(metric),
+            table,
+            protocol: RouteProtocol::Dhcp,
+            kind: RouteType::Unicast,
+        }
+    }
+
+    fn ipv4_path(routes: &[Route], rules: &[PolicyRule]) -> Option<PrimaryPath> {
+        select_primary_paths(routes, rules, &interfaces()).ipv4
+    }
+
+    #[test]
+    fn lowest_metric_default_route_wins() {
+        let routes = [
+            default_route(
+                3,
+                Some(Ipv4Addr::new(198, 51, 100, 1)),
+                600,
+                Route::MAIN_TABLE,
+            ),
+            default_route(2, Some(Ipv4Addr::new(192, 0, 2, 1)), 100, Route::MAIN_TABLE),
+        ];
+
+        let path = ipv4_path(&routes, &[]).unwrap();
+
+        assert_eq!(path.ifindex, 2);
+        assert_eq!(path.interface_name, "eth0");
+        assert_eq!(path.gateway, Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))));
+        assert_eq!(path.source, Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 10))));
+        assert_eq!(select_primary_paths(&routes, &[], &interfaces()).ipv6, None);
+    }
+
+    #[test]
+    fn interface_that_is_not_running_is_skipped() {
+        let routes = [
+            default_route(
+                3,
+                Some(Ipv4Addr::new(198, 51, 100, 1)),
+                600,
+                Route::MAIN_TABLE,
+            ),
+            default_route(2, Some(Ipv4Addr::new(192, 0, 2, 1)), 100, Route::MAIN_TABLE),
+        ];
+        let mut interfaces = interfaces();
+        interfaces.get_mut(&2).unwrap().flags.running = false;
+
+        let path = select_primary_paths(&routes, &[], &interfaces)
+            .ipv4
+            .unwrap();
+
+        assert_eq!(path.interface_name, "wlan0");
+    }
+
+    #[test]
+    fn multipath_default_route_uses_the_first_running_next_hop() {
+        let nexthop = |ifindex: u32, gateway: Ipv4Addr| NextHop {
+            gateway: Some(IpAddr::V4(gateway)),
+            ifindex,
+            weight: 1,
+        };
+        let routes = [Route {
+            gateway: None,
+            ifindex: None,
+            nexthops: vec![
+                nexthop(2, Ipv4Addr::new(192, 0, 2, 1)),
+                nexthop(3, Ipv4Addr::new(198, 51, 100, 1)),
+            ],
+            ..default_route(0, None, 100, Route::MAIN_TABLE)
+        }];
+        let mut interfaces = interfaces();
+        interfaces.get_mut(&2).unwrap().flags.running = false;
+
+        let path = select_primary_paths(&routes, &[], &interfaces)
+            .ipv4
+            .unwrap();
+
+        assert_eq!(path.interface_name, "wlan0");
+        assert_eq!(
+            path.gateway,
+            Some(IpAddr::V4(Ipv4Addr::new(198, 51, 100, 1)))
+        );
+        assert_eq!(
+            path.source,
+            Some(IpAddr::V4(Ipv4Addr::new(198, 51, 100, 10)))
+        );
+    }
+
+    #[test]
+    fn policy_rules_are_followed_in_priority_order() {
+        // The layout wg-quick uses: a table holding only the tunnel's default route, consulted
+        // before main, with main still used for everything more specific than a default route.
+        let routes = [
+            default_route(2, Some(Ipv4Addr::new(192, 0, 2, 1)), 100, Route::MAIN_TABLE),
+            default_route(4, None, 0, 51820),
+        ];
+        let mut rules = PolicyRule::defaults(false);
+        rules.push(PolicyRule {
+            suppress_prefixlen: Some(0),
+            ..PolicyRule::lookup(false, 32764, Route::MAIN_TABLE)
+        });
+        rules.push(PolicyRule::lookup(false, 32765, 51820));
+
+        let path = ipv4_path(&routes, &rules).unwrap();
+
+        assert_eq!(path.interface_name, "wg0");
+        assert_eq!(path.gateway, None);
+        assert_eq!(path.source, Some(IpAddr::V4(Ipv4Addr::new(10, 8, 0, 2))));
+
+        // Rules that only match some traffic, e.g. by firewall mark, do not apply.
+        rules.last_mut().unwrap().matches_any = false;
+        assert_eq!(ipv4_path(&routes, &rules).unwrap().interface_name, "eth0");
+    }
+
+    #[test]
+    fn goto_skips_the_rules_before_its_target() {
+        let routes = [
+            default_route(2, Some(Ipv4Addr::new(192, 0, 2, 1)), 100, Route::MAIN_TABLE),
+            default_route(4, None, 0, 51820),
+        ];
+        let mut rules = PolicyRule::defaults(false);
+        let rule = |priority, action| PolicyRule {
+            action,
+            ..PolicyRule::lookup(false, priority, 0)
+        };
+        rules.push(rule(100, PolicyAction::Nop));
+        rules.push(rule(200, PolicyAction::Goto(400)));
+        rules.push(PolicyRule::lookup(false, 300, 51820));
+
+        assert_eq!(ipv4_path(&routes, &rules).unwrap().interface_name, "eth0");
+
+        // Without the goto, the tunnel table is consulted before main.
+        rules.retain(|rule| rule.priority != 200);
+        assert_eq!(ipv4_path(&routes, &rules).unwrap().interface_name, "wg0");
+    }
+
+    #[test]
+    fn rejecting_rules_and_routes_leave_no_path() {
+        let mut routes = vec![default_route(
+            2,
+            Some(Ipv4Addr::new(192, 0, 2, 1)),
+            100,
+            Route::MAIN_TABLE,
+        )];
+        let mut rules = PolicyRule::defaults(false);
+        rules.push(PolicyRule {
+            action: PolicyAction::Reject,
+            ..PolicyRule::lookup(false, 100, 0)
+        });
+        assert_eq!(ipv4_path(&routes, &rules), None);
+
+        routes.push(Route {
+            kind: RouteType::Unreachable,
+            ifindex: None,
+            gateway: None,
+            ..default_route(0, None, 0, Route::MAIN_TABLE)
+        });
+        assert_eq!(ipv4_path(&routes, &PolicyRule::defaults(false)), None);
+    }
+
+    #[test]
+    fn source_is_chosen_from_the_gateway_subnet() {
+        let mut interfaces = interfaces();
+        let eth0 = interfaces.get_mut(&2).unwrap();
+        let secondary = IpRecord {
+            ip: IpAddr::V4(Ipv4Addr::new(203, 0, 113, 10)),
+            prefix_len: 24,
+        };
+        eth0.ips.insert(0, secondary.clone());
+        eth0.ips.push(IpRecord {
+            ip: IpAddr::V4(Ipv4Addr::new(192, 0, 2, 11)),
+            prefix_len: 24,
+        });
+        eth0.addr_info.insert(
+            IpRecord {
+                ip: IpAddr::V4(Ipv4Addr::new(192, 0, 2, 10)),
+                prefix_len: 24,
+            },
+            AddrInfo {
+                flags: AddrFlags {
+                    tentative: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        let routes = [default_route(
+            2,
+            Some(Ipv4Addr::new(192, 0, 2, 1)),
+            100,
+            Route::MAIN_TABLE,
+        )];
+
+        let path = select_primary_paths(&routes, &[], &interfaces)
+            .ipv4
+            .unwrap();
+        assert_eq!(path.source, Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 11))));
+
+        let routes = [Route {
+            source: Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 10))),
+            ..routes[0].clone()
+        }];
+        let path = select_primary_paths(&routes, &[], &interfaces)
+            .ipv4
+            .unwrap();
+        assert_eq!(path.source, Some(secondary.ip));
+    }
+
+    #[test]
+    fn only_changes_to_a_path_produce_updates() {
+        let mut cursor = PrimaryPathCursor::default();
+        let routes = [default_route(
+            2,
+            Some(Ipv4Addr::new(192, 0, 2, 1)),
+            100,
+            Route::MAIN_TABLE,
+        )];
+        let paths = select_primary_paths(&routes, &[], &interfaces());
+
+        let initial = cursor.advance(paths.clone()).unwrap();
+        assert!(initial.is_initial);
+        assert!(initial.ipv4_changed);
+        assert!(!initial.ipv6_changed);
+        assert_eq!(cursor.advance(paths), None);
+
+        let update = cursor.advance(PrimaryPaths::default()).unwrap();
+        assert!(!update.is_initial);
+        assert!(update.ipv4_changed);
+        assert!(!update.ipv6_changed);
+        assert_eq!(update.paths.ipv4, None);
+    }
+}
@@ -0,0 +1,342 @@
+#[cfg(any(target_os = "linux", test))]
+use std::collections::BTreeMap;
+use std::net::Ipv6Addr;
+use std::time::{Duration, Instant};
+
+/// DNS configuration learned from IPv6 router advertisements on one interface, from their
+/// RDNSS and DNSSL options (RFC 8106).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaDnsConfig {
+    /// Index of the interface the advertisements arrived on. Link-local servers are reached
+    /// through it.
+    pub ifindex: u32,
+    /// Recursive DNS servers, in the order routers advertised them.
+    pub servers: Vec<RaDnsServer>,
+    /// DNS search domains, in the order routers advertised them.
+    pub search: Vec<RaSearchDomain>,
+}
+
+/// A recursive DNS server advertised by a router.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaDnsServer {
+    pub ip: Ipv6Addr,
+    pub lifetime: RaLifetime,
+}
+
+/// A DNS search domain advertised by a router.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaSearchDomain {
+    pub domain: String,
+    pub lifetime: RaLifetime,
+}
+
+/// How long an advertised server or search domain may be used.
+///
+/// Each advertisement restarts the lifetime, so a refresh compares unequal even if the
+/// advertised lifetime is the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RaLifetime {
+    /// How long the entry remains valid after it was received. `None` if it does not expire.
+    pub valid: Option<Duration>,
+    /// When the advertisement carrying the entry was received.
+    pub received_at: Instant,
+}
+
+impl RaLifetime {
+    /// When the entry expires, or `None` if it does not.
+    pub fn valid_until(&self) -> Option<Instant> {
+        self.valid.map(|valid| self.received_at + valid)
+    }
+}
+
+/// Information delivered when DNS configuration learned from router advertisements changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaDnsUpdate {
+    /// Whether this is the initial update. It is always empty, because the kernel does not keep
+    /// advertised DNS options.
+    pub is_initial: bool,
+    /// The configuration of every interface with at least one server or search domain that has
+    /// not expired, sorted by interface index.
+    pub interfaces: Vec<RaDnsConfig>,
+    /// Indexes of the interfaces whose configuration changed, including lifetimes that were
+    /// refreshed and entries that expired.
+    pub changed: Vec<u32>,
+}
+
+/// The DNS options the kernel passes on from a router advertisement.
+#[cfg(any(target_os = "linux", test))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum RaDnsOption {
+    Servers {
+        ips: Vec<Ipv6Addr>,
+        lifetime: Option<Duration>,
+    },
+    SearchDomains {
+        domains: Vec<String>,
+        lifetime: Option<Duration>,
+    },
+}
+
+#[cfg(any(target_os = "linux", test))]
+const ND_OPT_RDNSS: u8 = 25;
+#[cfg(any(target_os = "linux", test))]
+const ND_OPT_DNSSL: u8 = 31;
+
+/// Decode an RDNSS or DNSSL neighbour discovery option, skipping other options.
+#[cfg(any(target_os = "linux", test))]
+pub(crate) fn parse_ra_dns_option(option: &[u8]) -> Option<RaDnsOption> {
+    // Type, length in units of 8 octets, two reserved octets and the lifetime in seconds.
+    let len = usize::from(*option.get(1)?) * 8;
+    let body = option.get(8..len)?;
+    let lifetime = match u32::from_be_bytes(option[4..8].try_into().ok()?) {
+        u32::MAX => None,
+        seconds => Some(Duration::from_secs(seconds.into())),
+    };
+    match option[0] {
+        ND_OPT_RDNSS => {
+            let ips = body
+                .chunks_exact(16)
+                .map(|octets| Ipv6Addr::from(<[u8; 16]>::try_from(octets).unwrap()))
+                .collect();
+            Some(RaDnsOption::Servers { ips, lifetime })
+        }
+        ND_OPT_DNSSL => Some(RaDnsOption::SearchDomains {
+            domains: parse_domain_names(body),
+            lifetime,
+        }),
+        _ => None,
+    }
+}
+
+/// Decode a sequence of uncompressed DNS names, which is padded with zeros to fill the option.
+#[cfg(any(target_os = "linux", test))]
+fn parse_domain_names(mut data: &[u8]) -> Vec<String> {
+    let mut domains = Vec::new();
+    let mut labels = Vec::new();
+    while let Some((&len, rest)) = data.split_first() {
+        let len = usize::from(len);
+        if len == 0 {
+            if labels.is_empty() {
+                break;
+            }
+            domains.push(labels.join("."));
+            labels.clear();
+        } else {
+            let Some(label) = rest.get(..len) else {
+                break;
+            };
+            labels.push(String::from_utf8_lossy(label).into_owned());
+        }
+        data = &rest[len..];
+    }
+    domains
+}
+
+/// Every interface's advertised DNS configuration, keyed by interface index.
+#[cfg(any(target_os = "linux", test))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct RaDnsList(pub(crate) BTreeMap<u32, RaDnsConfig>);
+
+#[cfg(any(target_os = "linux", test))]
+impl RaDnsList {
+    /// Add or refresh the entries in an option, or remove them if its lifetime is zero.
+    pub(crate) fn apply(&mut self, ifindex: u32, option: RaDnsOption, received_at: Instant) {
+        let config = self.0.entry(ifindex).or_insert_with(|| RaDnsConfig {
+            ifindex,
+            servers: Vec::new(),
+            search: Vec::new(),
+        });
+        // A refreshed entry keeps its place, so that the advertised order is preserved.
+        match option {
+            RaDnsOption::Servers { ips, lifetime } => {
+                let lifetime = RaLifetime {
+                    valid: lifetime,
+                    received_at,
+                };
+                for ip in ips {
+                    match config.servers.iter_mut().find(|server| server.ip == ip) {
+                        Some(server) => server.lifetime = lifetime,
+                        None => config.servers.push(RaDnsServer { ip, lifetime }),
+                    }
+                }
+                config
+                    .servers
+                    .retain(|server| server.lifetime.valid != Some(Duration::ZERO));
+            }
+            RaDnsOption::SearchDomains { domains, lifetime } => {
+                let lifetime = RaLifetime {
+                    valid: lifetime,
+                    received_at,
+                };
+                for domain in domains {
+                    match config
+                        .search
+                        .iter_mut()
+                        .find(|search| search.domain == domain)
+                    {
+                        Some(search) => search.lifetime = lifetime,
+                        None => config.search.push(RaSearchDomain { domain, lifetime }),
+                    }
+                }
+                config
+                    .search
+                    .retain(|search| search.lifetime.valid != Some(Duration::ZERO));
+            }
+        }
+        self.remove_empty();
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(crate) fn remove_interface(&mut self, ifindex: u32) {
+        self.0.remove(&ifindex);
+    }
+
+    /// Remove every entry whose lifetime ran out by `now`.
+    pub(crate) fn expire(&mut self, now: Instant) {
+        let live = |lifetime: &RaLifetime| lifetime.valid_until().is_none_or(|until| until > now);
+        for config in self.0.values_mut() {
+            config.servers.retain(|server| live(&server.lifetime));
+            config.search.retain(|search| live(&search.lifetime));
+        }
+        self.remove_empty();
+    }
+
+    /// When the next entry expires, if any does.
+    pub(crate) fn next_expiry(&self) -> Option<Instant> {
+        self.0
+            .values()
+            .flat_map(|config| {
+                let servers = config.servers.iter().map(|server| server.lifetime);
+                let search = config.search.iter().map(|search| search.lifetime);
+                servers.chain(search)
+            })
+            .filter_map(|lifetime| lifetime.valid_until())
+            .min()
+    }
+
+    fn remove_empty(&mut self) {
+        self.0
+            .retain(|_, config| !config.servers.is_empty() || !config.search.is_empty());
+    }
+}
+
+/// Turns successive snapshots into updates, like `NeighbourCursor` does for neighbours.
+#[cfg(any(target_os = "linux", test))]
+pub(crate) struct RaDnsCursor {
+    prev_list: RaDnsList,
+    initial_pending: bool,
+}
+
+#[cfg(any(target_os = "linux", test))]
+impl Default for RaDnsCursor {
+    fn default() -> Self {
+        Self {
+            prev_list: RaDnsList::default(),
+            initial_pending: true,
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", test))]
+impl RaDnsCursor {
+    pub(crate) fn advance(&mut self, new_list: RaDnsList) -> Option<RaDnsUpdate> {
+        let is_initial = std::mem::take(&mut self.initial_pending);
+        if !is_initial && new_list == self.prev_list {
+            return None;
+        }
+        let mut changed: Vec<u32> = new_list
+            .0
+            .keys()
+            .chain(self.prev_list.0.keys())
+            .copied()
+            .filter(|ifindex| new_list.0.get(ifindex) != self.prev_list.0.get(ifindex))
+            .collect();
+        changed.sort_unstable();
+        changed.dedup();
+        let update = RaDnsUpdate {
+            is_initial,
+            interfaces: new_list.0.values().cloned().collect(),
+            changed,
+        };
+        self.prev_list = new_list;
+        Some(update)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rdnss(lifetime: u32, ips: &[Ipv6Addr]) -> Vec<u8> {
+        let mut option = vec![ND_OPT_RDNSS, 1 + 2 * ips.len() as u8, 0, 0];
+        option.extend(lifetime.to_be_bytes());
+        for ip in ips {
+            option.extend(ip.octets());
+        }
+        option
+    }
+
+    #[test]
+    fn dns_options_are_decoded() {
+        let server: Ipv6Addr = "2001:db8::53".parse().unwrap();
+        assert_eq!(
+            parse_ra_dns_option(&rdnss(600, &[server])),
+            Some(RaDnsOption::Servers {
+                ips: vec![server],
+                lifetime: Some(Duration::from_secs(600)),
+            })
+        );
+
+        let mut dnssl = vec![ND_OPT_DNSSL, 5, 0, 0, 0xff, 0xff, 0xff, 0xff];
+        dnssl.extend(b"\x07example\x03com\x00\x03lab\x07example\x00");
+        dnssl.resize(40, 0);
+        assert_eq!(
+            parse_ra_dns_option(&dnssl),
+            Some(RaDnsOption::SearchDomains {
+                domains: vec!["example.com".to_string(), "lab.example".to_string()],
+                lifetime: None,
+            })
+        );
+
+        // A length that runs past the end of the data
+        let mut truncated = rdnss(600, &[server]);
+        truncated[1] = 5;
+        assert_eq!(parse_ra_dns_option(&truncated), None);
+    }
+
+    #[test]
+    fn servers_are_refreshed_withdrawn_and_expire() {
+        let first: Ipv6Addr = "2001:db8::1".parse().unwrap();
+        let second: Ipv6Addr = "2001:db8::2".parse().unwrap();
+        let servers = |ips: &[Ipv6Addr], seconds| RaDnsOption::Servers {
+            ips: ips.to_vec(),
+            lifetime: Some(Duration::from_secs(seconds)),
+        };
+        let start = Instant::now();
+        let mut list = RaDnsList::default();
+        let mut cursor = RaDnsCursor::default();
+        let initial = cursor.advance(list.clone()).unwrap();
+        assert!(initial.is_initial);
+        assert!(initial.interfaces.is_empty());
+
+        list.apply(2, servers(&[first, second], 600), start);
+        list.apply(3, servers(&[second], 60), start);
+        let update = cursor.advance(list.clone()).unwrap();
+        assert_eq!(update.changed, vec![2, 3]);
+        assert_eq!(update.interfaces[0].servers[1].ip, second);
+        assert_eq!(list.next_expiry(), Some(start + Duration::from_secs(60)));
+
+        // A lifetime of zero withdraws a server.
+        list.apply(2, servers(&[first], 0), start);
+        let update = cursor.advance(list.clone()).unwrap();
+        assert_eq!(update.changed, vec![2]);
+        assert_eq!(update.interfaces[0].servers.len(), 1);
+        assert_eq!(update.interfaces[0].servers[0].ip, second);
+
+        list.expire(start + Duration::from_secs(60));
+        let update = cursor.advance(list.clone()).unwrap();
+        assert_eq!(update.changed, vec![3]);
+        assert_eq!(update.interfaces.len(), 1);
+        assert_eq!(cursor.advance(list), None);
+    }
+}
@@ -0,0 +1,189 @@
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd};
+use std::time::Instant;
+
+use nix::errno::Errno;
+use nix::libc::{
+    itimerspec, timerfd_create, timerfd_settime, timespec, CLOCK_MONOTONIC, TFD_CLOEXEC,
+    TFD_NONBLOCK,
+};
+use nix::sys::socket::{recv, MsgFlags};
+use nix::unistd::read;
+
+use crate::netlink::{
+    messages, parse_link, parse_nduseropt, Message, ND_ROUTER_ADVERT, RTM_DELLINK, RTM_NEWNDUSEROPT,
+};
+use crate::ra_dns::{parse_ra_dns_option, RaDnsCursor, RaDnsList};
+use crate::watch::{open_netlink_event_socket, RTMGRP_LINK, RTMGRP_ND_USEROPT};
+use crate::watch_fd::{Drained, EventSocket, EventSocketOps, Snapshot, WatchHandle};
+use crate::{Error, RaDnsUpdate, WatchOptions};
+
+pub(crate) type AsyncRaDnsWatch = crate::watch_fd::AsyncWatch<RaDnsList>;
+pub(crate) type BlockingRaDnsWatch = crate::watch_fd::BlockingWatch<RaDnsList>;
+
+const EVENT_SOCKET_OPS: EventSocketOps<RaDnsList> = EventSocketOps {
+    open: open_event_socket,
+    drain: drain_event_socket,
+};
+
+impl Snapshot for RaDnsList {
+    type Update = RaDnsUpdate;
+    type Cursor = RaDnsCursor;
+
+    // The kernel passes the options on as they arrive and keeps no copy, so there is nothing to
+    // list. Every entry is learned from a notification.
+    fn fetch(_options: &WatchOptions) -> Result<Self, Error> {
+        Ok(RaDnsList::default())
+    }
+
+    fn cursor(_options: &WatchOptions) -> RaDnsCursor {
+        RaDnsCursor::default()
+    }
+
+    fn advance(cursor: &mut RaDnsCursor, list: RaDnsList) -> Option<RaDnsUpdate> {
+        cursor.advance(list)
+    }
+}
+
+pub(crate) fn watch_ra_dns_with_callback<F: FnMut(RaDnsUpdate) + Send + 'static>(
+    callback: F,
+    options: &WatchOptions,
+) -> Result<WatchHandle, Error> {
+    crate::watch_fd::watch_with_callback(callback, EVENT_SOCKET_OPS, options)
+}
+
+pub(crate) fn watch_ra_dns_async<A: crate::async_adapter::AsyncFdAdapter>(
+    options: &WatchOptions,
+) -> Result<AsyncRaDnsWatch, Error> {
+    crate::watch_fd::watch_async::<RaDnsList, A>(EVENT_SOCKET_OPS, options)
+}
+
+pub(crate) fn watch_ra_dns_blocking(options: &WatchOptions) -> Result<BlockingRaDnsWatch, Error> {
+    crate::watch_fd::watch_blocking(EVENT_SOCKET_OPS, options)
+}
+
+// The netlink socket and a timer for the next expiry are polled through one epoll descriptor, in
+// that order. Links are watched so that entries for a removed interface can be dropped.
+fn open_event_socket(options: &WatchOptions) -> Result<EventSocket, Error> {
+    let netlink = open_netlink_event_socket(options, RTMGRP_LINK | RTMGRP_ND_USEROPT)?;
+    let timer = unsafe { timerfd_create(CLOCK_MONOTONIC, TFD_NONBLOCK | TFD_CLOEXEC) };
+    if timer == -1 {
+        return Err(Error::Io(std::io::Error::last_os_error()));
+    }
+    let timer = unsafe { OwnedFd::from_raw_fd(timer) };
+    EventSocket::multiplex(vec![netlink, timer])
+}
+
+// Relisting would forget every learned entry, so lost notifications are not reported. The
+// entries that are kept stay valid until their lifetimes run out, and routers repeat their
+// advertisements well before then.
+fn drain_event_socket(_fd: BorrowedFd<'_>, sources: &[OwnedFd], list: &mut RaDnsList) -> Drained {
+    let [netlink, timer] = sources else {
+        return Drained::Applied;
+    };
+    let mut expirations = [0u8; 8];
+    let _ = read(timer.as_fd(), &mut expirations);
+
+    let mut buf = [0u8; 8192];
+    loop {
+        match recv(netlink.as_raw_fd(), &mut buf, MsgFlags::MSG_TRUNC) {
+            Ok(0) => break,
+            // A truncated datagram has lost notifications, which are tolerated as explained above
+            Ok(len) if len > buf.len() => {}
+            Ok(len) => {
+                let received_at = Instant::now();
+                for message in messages(&buf[..len]) {
+                    apply_message(list, &message, received_at);
+                }
+            }
+            Err(Errno::EAGAIN) => break,
+            Err(Errno::ENOBUFS) => continue,
+            Err(_) => break,
+        }
+    }
+
+    let now = Instant::now();
+    list.expire(now);
+    arm_timer(timer.as_fd(), list.next_expiry().map(|expiry| expiry - now));
+    Drained::Applied
+}
+
+/// Apply a neighbour discovery option or link notification to the learned entries.
+fn apply_message(list: &mut RaDnsList, message: &Message<'_>, received_at: Instant) {
+    match message.kind {
+        RTM_NEWNDUSEROPT => {
+            let Some(useropt) = parse_nduseropt(message.payload) else {
+                return;
+            };
+            if useropt.icmp_type != ND_ROUTER_ADVERT {
+                return;
+            }
+            if let Some(option) = parse_ra_dns_option(&useropt.option) {
+                list.apply(useropt.index, option, received_at);
+            }
+        }
+        // `parse_link` skips the AF_BRIDGE message sent when a port leaves a bridge, which does
+        // not mean that the interface itself is gone.
+        RTM_DELLINK => {
+            if let Some(link) = parse_link(message.payload) {
+                list.remove_interface(link.index);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Make the timer fire once after `delay`, or disarm it if there is nothing to expire.
+fn arm_timer(fd: BorrowedFd<'_>, delay: Option<std::time::Duration>) {
+    // An all-zero value disarms the timer, so the shortest delay is one nanosecond.
+    let value = match delay {
+        Some(delay) => timespec {
+            tv_sec: delay.as_secs() as _,
+            tv_nsec: delay.subsec_nanos().max(u32::from(delay.as_secs() == 0)) as _,
+        },
+        None => timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        },
+    };
+    let spec = itimerspec {
+        it_interval: timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        },
+        it_value: value,
+    };
+    unsafe { timerfd_settime(fd.as_raw_fd(), 0, &spec, std::ptr::null_mut()) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::netlink::tests::{link_payload, message};
+    use crate::ra_dns::RaDnsOption;
+    use std::time::Duration;
+
+    fn apply(list: &mut RaDnsList, kind: u16, payload: &[u8]) {
+        let buf = message(kind, payload);
+        let message = messages(&buf).next().unwrap();
+        apply_message(list, &message, Instant::now());
+    }
+
+    #[test]
+    fn only_removing_the_interface_drops_its_entries() {
+        let mut list = RaDnsList::default();
+        let option = RaDnsOption::Servers {
+            ips: vec!["2001:db8::53".parse().unwrap()],
+            lifetime: Some(Duration::from_secs(600)),
+        };
+        list.apply(2, option, Instant::now());
+        let before = list.clone();
+        let mut port = link_payload(2, 0, "eth0", &[]);
+        port[0] = 7;
+
+        apply(&mut list, RTM_DELLINK, &port);
+        assert_eq!(list, before);
+
+        apply(&mut list, RTM_DELLINK, &link_payload(2, 0, "eth0", &[]));
+        assert_eq!(list, RaDnsList::default());
+    }
+}
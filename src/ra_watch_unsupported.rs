@@ -0,0 +1,46 @@
+use crate::{Error, RaDnsUpdate, WatchOptions, WatchStats};
+
+/// Router advertisement DNS watchers cannot be created on this platform, so these types have no
+/// values.
+pub(crate) enum AsyncRaDnsWatch {}
+pub(crate) enum BlockingRaDnsWatch {}
+
+impl AsyncRaDnsWatch {
+    pub(crate) async fn changed(&mut self) -> RaDnsUpdate {
+        match *self {}
+    }
+
+    pub(crate) fn stats(&self) -> WatchStats {
+        match *self {}
+    }
+}
+
+impl BlockingRaDnsWatch {
+    pub(crate) fn changed(&mut self) -> RaDnsUpdate {
+        match *self {}
+    }
+
+    pub(crate) fn stats(&self) -> WatchStats {
+        match *self {}
+    }
+}
+
+/// Advertised DNS options are only implemented for Linux, where the kernel passes them on over
+/// rtnetlink.
+pub(crate) fn watch_ra_dns_with_callback<F: FnMut(RaDnsUpdate) + Send + 'static>(
+    _callback: F,
+    _options: &WatchOptions,
+) -> Result<crate::watch::WatchHandle, Error> {
+    Err(Error::Unsupported)
+}
+
+#[allow(clippy::extra_unused_type_parameters)]
+pub(crate) fn watch_ra_dns_async<A: crate::async_adapter::AsyncFdAdapter>(
+    _options: &WatchOptions,
+) -> Result<AsyncRaDnsWatch, Error> {
+    Err(Error::Unsupported)
+}
+
+pub(crate) fn watch_ra_dns_blocking(_options: &WatchOptions) -> Result<BlockingRaDnsWatch, Error> {
+    Err(Error::Unsupported)
+}
@@ -0,0 +1,230 @@
+use std::ffi::CString;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr};
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+use nix::errno::Errno;
+use nix::libc::{
+    inotify_add_watch, inotify_init1, IN_ATTRIB, IN_CLOEXEC, IN_CLOSE_WRITE, IN_CREATE, IN_DELETE,
+    IN_MOVED_FROM, IN_MOVED_TO, IN_NONBLOCK,
+};
+use nix::unistd::read;
+
+use crate::{DnsConfig, DnsOptions, Error};
+
+// The addresses systemd-resolved listens on for local clients.
+const RESOLVED_STUB_ADDRS: [Ipv4Addr; 2] =
+    [Ipv4Addr::new(127, 0, 0, 53), Ipv4Addr::new(127, 0, 0, 54)];
+
+pub(crate) fn dns_config(options: &DnsOptions) -> Result<DnsConfig, Error> {
+    let config = parse_resolv_conf(&fs::read_to_string(&options.resolv_conf_path)?);
+    let only_stub = !config.nameservers.is_empty()
+        && config.nameservers.iter().all(|ip| match ip {
+            IpAddr::V4(ip) => RESOLVED_STUB_ADDRS.contains(ip),
+            IpAddr::V6(_) => false,
+        });
+    if !only_stub {
+        return Ok(config);
+    }
+    // If systemd-resolved is not running after all, the stub is the best that is known.
+    match fs::read_to_string(&options.resolved_uplink_path) {
+        Ok(uplink) => Ok(DnsConfig {
+            uses_systemd_resolved: true,
+            ..parse_resolv_conf(&uplink)
+        }),
+        Err(_) => Ok(config),
+    }
+}
+
+/// Parse `resolv.conf` the way glibc does: `search` and `domain` replace each other so the last
+/// one wins, while `nameserver` and `options` lines accumulate.
+fn parse_resolv_conf(text: &str) -> DnsConfig {
+    let mut config = DnsConfig::default();
+    for line in text.lines() {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("nameserver") => {
+                // An IPv6 zone index cannot be represented in `IpAddr`
+                let addr = words.next().and_then(|addr| addr.split('%').next());
+                if let Some(ip) = addr.and_then(|addr| addr.parse().ok()) {
+                    config.nameservers.push(ip);
+                }
+            }
+            Some("search") => config.search = words.map(str::to_string).collect(),
+            Some("domain") => config.search = words.take(1).map(str::to_string).collect(),
+            Some("options") => config.options.extend(words.map(str::to_string)),
+            _ => {}
+        }
+    }
+    config
+}
+
+/// Open a non-blocking inotify instance watching everywhere the configuration can change.
+pub(crate) fn open_inotify(options: &DnsOptions) -> Result<OwnedFd, Error> {
+    let fd = unsafe { inotify_init1(IN_NONBLOCK | IN_CLOEXEC) };
+    if fd == -1 {
+        return Err(Error::Io(std::io::Error::last_os_error()));
+    }
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+    let parent = options.resolv_conf_path.parent().unwrap_or(Path::new("/"));
+    add_watch(fd.as_fd(), parent).map_err(|e| Error::Io(e.into()))?;
+    watch_dirs(fd.as_fd(), options);
+    Ok(fd)
+}
+
+/// Watch the directories that hold the configuration files and whatever they link to.
+///
+/// Files such as `/etc/resolv.conf` are usually replaced by renaming a new file over them, so
+/// their directories are watched rather than the files. This is repeated after each change in
+/// case a symlink now points somewhere new. Adding a watch that already exists has no effect.
+pub(crate) fn watch_dirs(fd: BorrowedFd<'_>, options: &DnsOptions) {
+    for path in [&options.resolv_conf_path, &options.resolved_uplink_path] {
+        let resolved = fs::canonicalize(path).ok();
+        for path in [Some(path), resolved.as_ref()].into_iter().flatten() {
+            if let Some(parent) = path.parent() {
+                // The systemd-resolved directory does not exist if it is not installed
+                let _ = add_watch(fd, parent);
+            }
+        }
+    }
+}
+
+fn add_watch(fd: BorrowedFd<'_>, dir: &Path) -> Result<(), Errno> {
+    let dir = if dir.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        dir.to_path_buf()
+    };
+    let dir = CString::new(dir.as_os_str().as_bytes()).map_err(|_| Errno::EINVAL)?;
+    let mask = IN_CLOSE_WRITE | IN_MOVED_TO | IN_MOVED_FROM | IN_CREATE | IN_DELETE | IN_ATTRIB;
+    let wd = unsafe { inotify_add_watch(fd.as_raw_fd(), dir.as_ptr(), mask) };
+    if wd == -1 {
+        return Err(Errno::last());
+    }
+    Ok(())
+}
+
+/// Read every queued inotify event, returning whether there were any.
+///
+/// The events themselves are not needed, because the whole configuration is read again.
+pub(crate) fn drain_inotify(fd: BorrowedFd<'_>) -> bool {
+    let mut buf = [0u8; 4096];
+    let mut changed = false;
+    loop {
+        match read(fd, &mut buf) {
+            Ok(0) => break,
+            Ok(_) => changed = true,
+            Err(Errno::EINTR) => continue,
+            Err(_) => break,
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("netwatcher-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        fn options(&self) -> DnsOptions {
+            DnsOptions::default()
+                .resolv_conf_path(self.0.join("resolv.conf"))
+                .resolved_uplink_path(self.0.join("resolve").join("resolv.conf"))
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn resolv_conf_is_parsed() {
+        let config = parse_resolv_conf(
+            "# Generated by NetworkManager\n\
+             domain corp.example\n\
+             search example.com lab.example.com\n\
+             nameserver 192.0.2.53\n\
+             ; nameserver 192.0.2.99\n\
+             nameserver fe80::1%eth0\n\
+             nameserver not-an-address\n\
+             options edns0\n\
+             options timeout:2 rotate\n",
+        );
+
+        assert_eq!(
+            config.nameservers,
+            vec![
+                "192.0.2.53".parse::<IpAddr>().unwrap(),
+                "fe80::1".parse().unwrap(),
+            ]
+        );
+        assert_eq!(config.search, vec!["example.com", "lab.example.com"]);
+        assert_eq!(config.options, vec!["edns0", "timeout:2", "rotate"]);
+        assert!(!config.uses_systemd_resolved);
+    }
+
+    #[test]
+    fn resolved_stub_is_followed_to_the_uplink() {
+        let dir = TempDir::new("resolv-stub");
+        let options = dir.options();
+        fs::write(
+            &options.resolv_conf_path,
+            "nameserver 127.0.0.53\noptions edns0 trust-ad\nsearch home.example\n",
+        )
+        .unwrap();
+
+        // Without systemd-resolved's own file the stub is reported as is.
+        let config = dns_config(&options).unwrap();
+        assert_eq!(config.nameservers, vec![IpAddr::from([127, 0, 0, 53])]);
+        assert!(!config.uses_systemd_resolved);
+
+        fs::create_dir(dir.0.join("resolve")).unwrap();
+        fs::write(
+            &options.resolved_uplink_path,
+            "nameserver 192.0.2.1\nnameserver 2001:db8::1\nsearch home.example\n",
+        )
+        .unwrap();
+        let config = dns_config(&options).unwrap();
+        assert_eq!(
+            config.nameservers,
+            vec![IpAddr::from([192, 0, 2, 1]), "2001:db8::1".parse().unwrap()]
+        );
+        assert_eq!(config.search, vec!["home.example"]);
+        assert!(config.options.is_empty());
+        assert!(config.uses_systemd_resolved);
+    }
+
+    #[test]
+    fn replacing_the_file_is_noticed() {
+        let dir = TempDir::new("resolv-inotify");
+        let options = dir.options();
+        fs::write(&options.resolv_conf_path, "nameserver 192.0.2.1\n").unwrap();
+        let fd = open_inotify(&options).unwrap();
+        assert!(!drain_inotify(fd.as_fd()));
+
+        let replacement = dir.0.join("resolv.conf.new");
+        fs::write(&replacement, "nameserver 192.0.2.2\n").unwrap();
+        fs::rename(&replacement, &options.resolv_conf_path).unwrap();
+
+        assert!(drain_inotify(fd.as_fd()));
+        assert!(!drain_inotify(fd.as_fd()));
+        assert_eq!(
+            dns_config(&options).unwrap().nameservers,
+            vec![IpAddr::from([192, 0, 2, 2])]
+        );
+    }
+}
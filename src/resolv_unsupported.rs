@@ -0,0 +1,6 @@
+use crate::{DnsConfig, DnsOptions, Error};
+
+/// DNS configuration is only implemented for Linux, where it is read from `resolv.conf`.
+pub(crate) fn dns_config(_options: &DnsOptions) -> Result<DnsConfig, Error> {
+    Err(Error::Unsupported)
+}
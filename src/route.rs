@@ -0,0 +1,367 @@
+use std::net::IpAddr;
+
+#[cfg(any(target_os = "linux", test))]
+use crate::keyed::{Keyed, KeyedCursor, KeyedDiff, KeyedList};
+use crate::{Interface, IpRecord};
+
+/// A route in one of the kernel's routing tables, as returned by `list_routes`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Route {
+    /// The destination network. A default route has the unspecified address and a prefix length
+    /// of 0.
+    pub destination: IpAddr,
+    /// Prefix length of the destination network.
+    pub prefix_len: u8,
+    /// The next hop, if traffic is forwarded to a router rather than delivered on the link.
+    pub gateway: Option<IpAddr>,
+    /// Index of the interface traffic leaves through.
+    ///
+    /// Multipath routes, which spread traffic over several next hops, report no interface or
+    /// gateway here. Their next hops are in `nexthops` instead.
+    pub ifindex: Option<u32>,
+    /// The next hops of a multipath route, in the order the kernel reports them. Empty for an
+    /// ordinary route.
+    pub nexthops: Vec<NextHop>,
+    /// The source address preferred for traffic using this route.
+    pub source: Option<IpAddr>,
+    /// The route's priority. Among routes to the same destination, the lowest metric wins.
+    pub metric: Option<u32>,
+    /// The routing table the route belongs to, e.g. `Route::MAIN_TABLE`.
+    pub table: u32,
+    /// What installed the route.
+    pub protocol: RouteProtocol,
+    /// What happens to traffic that matches the route.
+    pub kind: RouteType,
+}
+
+impl Route {
+    /// The table that ordinary routes are added to.
+    pub const MAIN_TABLE: u32 = 254;
+    /// The table the kernel maintains for local and broadcast addresses.
+    pub const LOCAL_TABLE: u32 = 255;
+
+    /// Whether this route covers every destination of its address family.
+    pub fn is_default(&self) -> bool {
+        self.prefix_len == 0
+    }
+}
+
+#[cfg(any(target_os = "linux", test))]
+impl Keyed for Route {
+    type Key = (u32, IpAddr, u8, Option<u32>);
+    type Update = RouteUpdate;
+
+    /// What identifies a route within the routing tables, so that a change to anything else is
+    /// a modification of the same route.
+    fn key(&self) -> Self::Key {
+        (self.table, self.destination, self.prefix_len, self.metric)
+    }
+
+    fn update(is_initial: bool, routes: Vec<Route>, diff: KeyedDiff<Route>) -> RouteUpdate {
+        RouteUpdate {
+            is_initial,
+            routes,
+            diff: RouteDiff {
+                added: diff.added,
+                removed: diff.removed,
+                modified: diff
+                    .modified
+                    .into_iter()
+                    .map(|(before, after)| ModifiedRoute { before, after })
+                    .collect(),
+            },
+        }
+    }
+}
+
+/// One of the next hops that a multipath route spreads traffic over.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NextHop {
+    /// The router traffic is forwarded to, if traffic is not delivered on the link.
+    pub gateway: Option<IpAddr>,
+    /// Index of the interface traffic leaves through.
+    pub ifindex: u32,
+    /// The share of traffic this next hop receives relative to the others, from 1 to 256.
+    pub weight: u16,
+}
+
+/// The route the kernel would use to reach one destination, as returned by `route_to`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteLookup {
+    /// The interface that traffic leaves through. This is the loopback interface for a
+    /// destination on this host.
+    pub interface: Interface,
+    /// The next hop, if traffic is forwarded to a router rather than delivered on the link.
+    pub gateway: Option<IpAddr>,
+    /// The source address that new connections to the destination will use.
+    pub source: Option<IpAddr>,
+    /// The record for `source` as it appears in `Interface::ips`, so that its prefix length is
+    /// known. The address usually belongs to `interface`, but may be assigned to another one.
+    pub source_record: Option<IpRecord>,
+}
+
+/// What installed a route, from the kernel's `RTPROT_*` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RouteProtocol {
+    /// Installed by an ICMP redirect.
+    Redirect,
+    /// Installed by the kernel, e.g. the prefix route for an address.
+    Kernel,
+    /// Installed during boot, which is also what `ip route add` uses by default.
+    Boot,
+    /// Installed by an administrator.
+    Static,
+    /// Learned from an IPv6 router advertisement.
+    Ra,
+    /// Installed by a DHCP client.
+    Dhcp,
+    /// A protocol value with no dedicated variant, e.g. one used by a routing daemon.
+    Other(u8),
+}
+
+/// What happens to traffic that matches a route, from the kernel's `RTN_*` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RouteType {
+    /// Forwarded to the gateway or delivered on the link.
+    Unicast,
+    /// Delivered to this host.
+    Local,
+    /// Sent as a broadcast on the link.
+    Broadcast,
+    /// Delivered to this host as an anycast address.
+    Anycast,
+    /// Sent as multicast.
+    Multicast,
+    /// Silently discarded.
+    Blackhole,
+    /// Discarded with a "host unreachable" error.
+    Unreachable,
+    /// Discarded with an "administratively prohibited" error.
+    Prohibit,
+    /// Lookup continues in the next table selected by the routing rules.
+    Throw,
+    /// A type value with no dedicated variant.
+    Other(u8),
+}
+
+/// Information delivered when the routing tables change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteUpdate {
+    /// Whether this update represents the initial existing routes.
+    pub is_initial: bool,
+    /// Every current route, sorted by destination.
+    pub routes: Vec<Route>,
+    /// The changes from the preceding update to `routes`.
+    pub diff: RouteDiff,
+}
+
+/// What changed between one `RouteUpdate` and the next.
+///
+/// For an initial update, every current route is listed as added.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RouteDiff {
+    /// Routes that appeared.
+    pub added: Vec<Route>,
+    /// Routes that disappeared, containing their last known state.
+    pub removed: Vec<Route>,
+    /// Routes whose table, destination and metric stayed the same but whose other attributes
+    /// changed, e.g. a route replaced with a new gateway.
+    pub modified: Vec<ModifiedRoute>,
+}
+
+/// The previous and current state of a route that was modified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModifiedRoute {
+    pub before: Route,
+    pub after: Route,
+}
+
+/// A snapshot of every route, kept sorted so that equal tables compare equal.
+#[cfg(any(target_os = "linux", test))]
+pub(crate) type RouteList = KeyedList<Route>;
+
+#[cfg(any(target_os = "linux", test))]
+pub(crate) type RouteCursor = KeyedCursor<Route>;
+
+#[cfg(any(target_os = "linux", test))]
+impl RouteList {
+    /// Add a route announced by an `RTM_NEWROUTE`. A replacement takes the place of the route
+    /// with the same key, and so does any IPv6 route, because a multipath IPv6 route is
+    /// announced again as a whole whenever it gains a next hop. Returns false when several
+    /// routes share that key, so it is unknown which one was replaced.
+    pub(crate) fn insert(&mut self, route: Route, replace: bool) -> bool {
+        if replace || route.destination.is_ipv6() {
+            let same_key: Vec<usize> = (0..self.0.len())
+                .filter(|&index| self.0[index].key() == route.key())
+                .collect();
+            match same_key[..] {
+                [] => {}
+                [index] => {
+                    self.0.remove(index);
+                }
+                _ => return false,
+            }
+        }
+        self.0.push(route);
+        self.0.sort();
+        self.0.dedup();
+        true
+    }
+
+    /// Remove a route announced by an `RTM_DELROUTE`. A route that is already gone is ignored,
+    /// but returns false when the route with that key does not match exactly, as when only some
+    /// of the next hops of an IPv6 multipath route were deleted.
+    pub(crate) fn remove(&mut self, route: &Route) -> bool {
+        if let Some(index) = self.0.iter().position(|existing| existing == route) {
+            self.0.remove(index);
+            return true;
+        }
+        !self.0.iter().any(|existing| existing.key() == route.key())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    fn route(last_octet: u8, gateway: u8, metric: u32) -> Route {
+        Route {
+            destination: IpAddr::V4(Ipv4Addr::new(198, 51, 100, last_octet)),
+            prefix_len: 32,
+            gateway: Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, gateway))),
+            ifindex: Some(2),
+            nexthops: Vec::new(),
+            source: None,
+            metric: Some(metric),
+            table: Route::MAIN_TABLE,
+            protocol: RouteProtocol::Static,
+            kind: RouteType::Unicast,
+        }
+    }
+
+    #[test]
+    fn initial_update_adds_every_route_and_repeats_are_suppressed() {
+        let mut cursor = RouteCursor::default();
+        let list = RouteList::new(vec![route(2, 1, 0), route(1, 1, 0)]);
+
+        let update = cursor.advance(list.clone()).unwrap();
+
+        assert!(update.is_initial);
+        assert_eq!(update.routes, vec![route(1, 1, 0), route(2, 1, 0)]);
+        assert_eq!(update.diff.added, update.routes);
+        assert_eq!(cursor.advance(list), None);
+    }
+
+    #[test]
+    fn changed_gateway_is_a_modification() {
+        let mut cursor = RouteCursor::default();
+        cursor.advance(RouteList::new(vec![route(1, 1, 0), route(2, 1, 0)]));
+
+        let update = cursor
+            .advance(RouteList::new(vec![route(1, 9, 0), route(3, 1, 0)]))
+            .unwrap();
+
+        assert!(!update.is_initial);
+        assert_eq!(update.diff.added, vec![route(3, 1, 0)]);
+        assert_eq!(update.diff.removed, vec![route(2, 1, 0)]);
+        assert_eq!(
+            update.diff.modified,
+            vec![ModifiedRoute {
+                before: route(1, 1, 0),
+                after: route(1, 9, 0),
+            }]
+        );
+    }
+
+    #[test]
+    fn routes_with_a_different_metric_are_distinct() {
+        let mut cursor = RouteCursor::default();
+        cursor.advance(RouteList::new(vec![route(1, 1, 100)]));
+
+        let update = cursor
+            .advance(RouteList::new(vec![route(1, 1, 100), route(1, 2, 200)]))
+            .unwrap();
+
+        assert_eq!(update.diff.added, vec![route(1, 2, 200)]);
+        assert!(update.diff.removed.is_empty());
+        assert!(update.diff.modified.is_empty());
+    }
+
+    #[test]
+    fn changed_nexthop_of_a_multipath_route_is_a_modification() {
+        let multipath = |second_gateway: u8| Route {
+            gateway: None,
+            ifindex: None,
+            nexthops: [(1, 2), (second_gateway, 3)]
+                .into_iter()
+                .map(|(gateway, ifindex)| NextHop {
+                    gateway: Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, gateway))),
+                    ifindex,
+                    weight: 1,
+                })
+                .collect(),
+            ..route(1, 1, 0)
+        };
+        let mut cursor = RouteCursor::default();
+        cursor.advance(RouteList::new(vec![multipath(5)]));
+
+        let update = cursor.advance(RouteList::new(vec![multipath(6)])).unwrap();
+
+        assert_eq!(
+            update.diff.modified,
+            vec![ModifiedRoute {
+                before: multipath(5),
+                after: multipath(6),
+            }]
+        );
+    }
+
+    #[test]
+    fn replacement_takes_the_place_of_the_route_with_the_same_key() {
+        let mut list = RouteList::new(vec![route(1, 1, 0), route(2, 1, 0)]);
+
+        assert!(list.insert(route(1, 5, 0), true));
+
+        assert_eq!(list, RouteList::new(vec![route(1, 5, 0), route(2, 1, 0)]));
+    }
+
+    #[test]
+    fn appended_ipv4_route_keeps_the_route_with_the_same_key() {
+        let mut list = RouteList::new(vec![route(1, 1, 0)]);
+
+        assert!(list.insert(route(1, 5, 0), false));
+        assert_eq!(list, RouteList::new(vec![route(1, 1, 0), route(1, 5, 0)]));
+
+        // Which of the two a replacement took the place of is unknown
+        assert!(!list.insert(route(1, 7, 0), true));
+    }
+
+    #[test]
+    fn ipv6_route_takes_the_place_of_the_route_with_the_same_key() {
+        let ipv6 = |gateway: u16| Route {
+            destination: IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            gateway: Some(IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, gateway))),
+            ..route(0, 0, 0)
+        };
+        let mut list = RouteList::new(vec![ipv6(1)]);
+
+        assert!(list.insert(ipv6(2), false));
+
+        assert_eq!(list, RouteList::new(vec![ipv6(2)]));
+    }
+
+    #[test]
+    fn removal_must_match_the_route_exactly() {
+        let mut list = RouteList::new(vec![route(1, 1, 0), route(2, 1, 0)]);
+
+        assert!(list.remove(&route(1, 1, 0)));
+        assert_eq!(list, RouteList::new(vec![route(2, 1, 0)]));
+
+        // Already gone
+        assert!(list.remove(&route(1, 1, 0)));
+        // The route with that key is different from the one that was deleted
+        assert!(!list.remove(&route(2, 5, 0)));
+        assert_eq!(list, RouteList::new(vec![route(2, 1, 0)]));
+    }
+}
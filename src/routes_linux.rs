@@ -0,0 +1,443 @@
+use std::net::IpAddr;
+use std::os::fd::{AsRawFd, BorrowedFd, OwnedFd};
+
+use nix::errno::Errno;
+use nix::sys::socket::{recv, MsgFlags};
+
+use crate::netlink::{
+    messages, parse_addr, parse_link, parse_route, Message, RouteMessage, RouteSocket, RuleMessage,
+    FIB_RULE_INVERT, FR_ACT_BLACKHOLE, FR_ACT_GOTO, FR_ACT_NOP, FR_ACT_PROHIBIT, FR_ACT_TO_TBL,
+    FR_ACT_UNREACHABLE, IFF_UP, NLM_F_REPLACE, RTM_DELADDR, RTM_DELLINK, RTM_DELROUTE, RTM_NEWLINK,
+    RTM_NEWROUTE,
+};
+use crate::primary::{select_primary_paths, PolicyAction, PolicyRule, PrimaryPathCursor};
+use crate::route::{RouteCursor, RouteList};
+use crate::rule::{RuleCursor, RuleList};
+use crate::watch::{
+    open_netlink_event_socket, RTMGRP_IPV4_IFADDR, RTMGRP_IPV4_ROUTE, RTMGRP_IPV4_RULE,
+    RTMGRP_IPV6_IFADDR, RTMGRP_IPV6_ROUTE, RTMGRP_IPV6_RULE, RTMGRP_LINK,
+};
+use crate::watch_fd::{Drained, EventSocket, EventSocketOps, Snapshot, WatchHandle};
+use crate::{
+    Error, IpRecord, NextHop, PrimaryPathUpdate, PrimaryPaths, Route, RouteLookup, RouteProtocol,
+    RouteType, RouteUpdate, Rule, RuleAction, RuleUpdate, WatchOptions,
+};
+
+pub(crate) type AsyncRouteWatch = crate::watch_fd::AsyncWatch<RouteList>;
+pub(crate) type BlockingRouteWatch = crate::watch_fd::BlockingWatch<RouteList>;
+pub(crate) type AsyncPrimaryPathWatch = crate::watch_fd::AsyncWatch<PrimaryPaths>;
+pub(crate) type BlockingPrimaryPathWatch = crate::watch_fd::BlockingWatch<PrimaryPaths>;
+pub(crate) type AsyncRuleWatch = crate::watch_fd::AsyncWatch<RuleList>;
+pub(crate) type BlockingRuleWatch = crate::watch_fd::BlockingWatch<RuleList>;
+
+const EVENT_SOCKET_OPS: EventSocketOps<RouteList> = EventSocketOps {
+    open: open_event_socket,
+    drain: drain_event_socket,
+};
+
+const PRIMARY_PATH_EVENT_SOCKET_OPS: EventSocketOps<PrimaryPaths> = EventSocketOps {
+    open: open_primary_path_event_socket,
+    drain: drain_relisting_event_socket,
+};
+
+const RULE_EVENT_SOCKET_OPS: EventSocketOps<RuleList> = EventSocketOps {
+    open: open_rule_event_socket,
+    drain: drain_relisting_event_socket,
+};
+
+pub(crate) fn list_routes() -> Result<Vec<Route>, Error> {
+    fetch_routes().map(|list| list.0)
+}
+
+fn fetch_routes() -> Result<RouteList, Error> {
+    let routes = RouteSocket::open()?
+        .dump_routes()?
+        .into_iter()
+        .map(route_from_message)
+        .collect();
+    Ok(RouteList::new(routes))
+}
+
+pub(crate) fn list_rules() -> Result<Vec<Rule>, Error> {
+    fetch_rules().map(|list| list.0)
+}
+
+fn fetch_rules() -> Result<RuleList, Error> {
+    let rules = RouteSocket::open()?
+        .dump_rules()?
+        .ok_or(Error::Unsupported)?
+        .iter()
+        .map(rule_from_message)
+        .collect();
+    Ok(RuleList::new(rules))
+}
+
+pub(crate) fn route_to(destination: IpAddr) -> Result<Option<RouteLookup>, Error> {
+    let Some(route) = RouteSocket::open()?.get_route(destination)? else {
+        return Ok(None);
+    };
+    let kind = route_type(route.kind);
+    if matches!(
+        kind,
+        RouteType::Blackhole | RouteType::Unreachable | RouteType::Prohibit
+    ) {
+        return Ok(None);
+    }
+    let ifindex = route
+        .oif
+        .ok_or_else(|| Error::Netlink("route lookup has no interface".to_string()))?;
+    let mut interfaces = crate::list::list_interfaces()?.0;
+    let source_record = route.prefsrc.and_then(|source| {
+        interfaces
+            .values()
+            .flat_map(|interface| &interface.ips)
+            .find(|record| record.ip == source)
+            .cloned()
+    });
+    let interface = interfaces
+        .remove(&ifindex)
+        .ok_or_else(|| Error::Netlink(format!("interface {ifindex} disappeared")))?;
+    Ok(Some(RouteLookup {
+        interface,
+        gateway: route.gateway,
+        source: route.prefsrc,
+        source_record,
+    }))
+}
+
+pub(crate) fn primary_paths() -> Result<PrimaryPaths, Error> {
+    let mut socket = RouteSocket::open()?;
+    let routes: Vec<Route> = socket
+        .dump_routes()?
+        .into_iter()
+        .map(route_from_message)
+        .collect();
+    // A kernel built without policy routing has no rules to dump, and behaves as if it had the
+    // default ones.
+    let rules: Vec<PolicyRule> = socket
+        .dump_rules()?
+        .unwrap_or_default()
+        .iter()
+        .map(policy_rule)
+        .collect();
+    let interfaces = crate::list::list_interfaces()?.0;
+    Ok(select_primary_paths(&routes, &rules, &interfaces))
+}
+
+impl Snapshot for RouteList {
+    type Update = RouteUpdate;
+    type Cursor = RouteCursor;
+
+    fn fetch(_options: &WatchOptions) -> Result<Self, Error> {
+        fetch_routes()
+    }
+
+    fn cursor(_options: &WatchOptions) -> RouteCursor {
+        RouteCursor::default()
+    }
+
+    fn advance(cursor: &mut RouteCursor, list: RouteList) -> Option<RouteUpdate> {
+        cursor.advance(list)
+    }
+}
+
+impl Snapshot for PrimaryPaths {
+    type Update = PrimaryPathUpdate;
+    type Cursor = PrimaryPathCursor;
+
+    fn fetch(_options: &WatchOptions) -> Result<Self, Error> {
+        primary_paths()
+    }
+
+    fn cursor(_options: &WatchOptions) -> PrimaryPathCursor {
+        PrimaryPathCursor::default()
+    }
+
+    fn advance(cursor: &mut PrimaryPathCursor, paths: PrimaryPaths) -> Option<PrimaryPathUpdate> {
+        cursor.advance(paths)
+    }
+}
+
+impl Snapshot for RuleList {
+    type Update = RuleUpdate;
+    type Cursor = RuleCursor;
+
+    fn fetch(_options: &WatchOptions) -> Result<Self, Error> {
+        fetch_rules()
+    }
+
+    fn cursor(_options: &WatchOptions) -> RuleCursor {
+        RuleCursor::default()
+    }
+
+    fn advance(cursor: &mut RuleCursor, list: RuleList) -> Option<RuleUpdate> {
+        cursor.advance(list)
+    }
+}
+
+pub(crate) fn watch_routes_with_callback<F: FnMut(RouteUpdate) + Send + 'static>(
+    callback: F,
+    options: &WatchOptions,
+) -> Result<WatchHandle, Error> {
+    crate::watch_fd::watch_with_callback(callback, EVENT_SOCKET_OPS, options)
+}
+
+pub(crate) fn watch_routes_async<A: crate::async_adapter::AsyncFdAdapter>(
+    options: &WatchOptions,
+) -> Result<AsyncRouteWatch, Error> {
+    crate::watch_fd::watch_async::<RouteList, A>(EVENT_SOCKET_OPS, options)
+}
+
+pub(crate) fn watch_routes_blocking(options: &WatchOptions) -> Result<BlockingRouteWatch, Error> {
+    crate::watch_fd::watch_blocking(EVENT_SOCKET_OPS, options)
+}
+
+pub(crate) fn watch_primary_paths_with_callback<F: FnMut(PrimaryPathUpdate) + Send + 'static>(
+    callback: F,
+    options: &WatchOptions,
+) -> Result<WatchHandle, Error> {
+    crate::watch_fd::watch_with_callback(callback, PRIMARY_PATH_EVENT_SOCKET_OPS, options)
+}
+
+pub(crate) fn watch_primary_paths_async<A: crate::async_adapter::AsyncFdAdapter>(
+    options: &WatchOptions,
+) -> Result<AsyncPrimaryPathWatch, Error> {
+    crate::watch_fd::watch_async::<PrimaryPaths, A>(PRIMARY_PATH_EVENT_SOCKET_OPS, options)
+}
+
+pub(crate) fn watch_primary_paths_blocking(
+    options: &WatchOptions,
+) -> Result<BlockingPrimaryPathWatch, Error> {
+    crate::watch_fd::watch_blocking(PRIMARY_PATH_EVENT_SOCKET_OPS, options)
+}
+
+pub(crate) fn watch_rules_with_callback<F: FnMut(RuleUpdate) + Send + 'static>(
+    callback: F,
+    options: &WatchOptions,
+) -> Result<WatchHandle, Error> {
+    crate::watch_fd::watch_with_callback(callback, RULE_EVENT_SOCKET_OPS, options)
+}
+
+pub(crate) fn watch_rules_async<A: crate::async_adapter::AsyncFdAdapter>(
+    options: &WatchOptions,
+) -> Result<AsyncRuleWatch, Error> {
+    crate::watch_fd::watch_async::<RuleList, A>(RULE_EVENT_SOCKET_OPS, options)
+}
+
+pub(crate) fn watch_rules_blocking(options: &WatchOptions) -> Result<BlockingRuleWatch, Error> {
+    crate::watch_fd::watch_blocking(RULE_EVENT_SOCKET_OPS, options)
+}
+
+// IPv4 routes that go away because their interface or address did are removed without an
+// RTM_DELROUTE, so link and address changes are watched as well.
+fn open_event_socket(options: &WatchOptions) -> Result<EventSocket, Error> {
+    open_netlink_event_socket(
+        options,
+        RTMGRP_LINK
+            | RTMGRP_IPV4_IFADDR
+            | RTMGRP_IPV6_IFADDR
+            | RTMGRP_IPV4_ROUTE
+            | RTMGRP_IPV6_ROUTE,
+    )
+    .map(EventSocket::from)
+}
+
+// The primary path also depends on the policy rules and on which interfaces are running.
+fn open_primary_path_event_socket(options: &WatchOptions) -> Result<EventSocket, Error> {
+    open_netlink_event_socket(
+        options,
+        RTMGRP_LINK
+            | RTMGRP_IPV4_IFADDR
+            | RTMGRP_IPV6_IFADDR
+            | RTMGRP_IPV4_ROUTE
+            | RTMGRP_IPV6_ROUTE
+            | RTMGRP_IPV4_RULE
+            | RTMGRP_IPV6_RULE,
+    )
+    .map(EventSocket::from)
+}
+
+fn open_rule_event_socket(options: &WatchOptions) -> Result<EventSocket, Error> {
+    open_netlink_event_socket(options, RTMGRP_IPV4_RULE | RTMGRP_IPV6_RULE).map(EventSocket::from)
+}
+
+fn drain_event_socket(fd: BorrowedFd<'_>, _sources: &[OwnedFd], list: &mut RouteList) -> Drained {
+    let mut buf = [0u8; 8192];
+    let mut drained = Drained::Applied;
+    loop {
+        match recv(fd.as_raw_fd(), &mut buf, MsgFlags::MSG_TRUNC) {
+            Ok(0) => break,
+            // A truncated datagram has lost notifications
+            Ok(len) if len > buf.len() => drained = drained.max(Drained::Relist),
+            Ok(len) => {
+                // Once a relist is required there is no point applying the rest, but the socket
+                // must still be emptied so that it stops polling readable.
+                if drained == Drained::Applied {
+                    for message in messages(&buf[..len]) {
+                        drained = apply_message(list, &message);
+                        if drained == Drained::Relist {
+                            break;
+                        }
+                    }
+                }
+            }
+            Err(Errno::EAGAIN) => break,
+            Err(Errno::ENOBUFS) => drained = Drained::Overrun,
+            Err(_) => {
+                drained = drained.max(Drained::Relist);
+                break;
+            }
+        }
+    }
+    drained
+}
+
+/// Apply a route, link or address notification to a list built by `fetch_routes`.
+///
+/// When a link goes down, or an IPv4 address is removed, the kernel flushes the IPv4 routes
+/// that depended on it without announcing them, so the routes are listed again. Links that
+/// come up and new addresses announce every route they add.
+fn apply_message(list: &mut RouteList, message: &Message<'_>) -> Drained {
+    let applied = match message.kind {
+        RTM_NEWROUTE | RTM_DELROUTE => {
+            let Some(route) = parse_route(message.payload).filter(|route| !route.cloned) else {
+                return Drained::Applied;
+            };
+            let route = route_from_message(route);
+            if message.kind == RTM_NEWROUTE {
+                list.insert(route, message.flags & NLM_F_REPLACE != 0)
+            } else {
+                list.remove(&route)
+            }
+        }
+        RTM_NEWLINK => parse_link(message.payload).is_none_or(|link| link.flags & IFF_UP != 0),
+        RTM_DELLINK => parse_link(message.payload).is_none(),
+        RTM_DELADDR => parse_addr(message.payload).is_none_or(|addr| addr.ip.is_ipv6()),
+        _ => true,
+    };
+    if applied {
+        Drained::Applied
+    } else {
+        Drained::Relist
+    }
+}
+
+// The primary paths depend on routes, rules and interfaces together, and rules change rarely,
+// so these notifications are only used as a wakeup and everything is dumped again.
+fn drain_relisting_event_socket<S>(
+    fd: BorrowedFd<'_>,
+    _sources: &[OwnedFd],
+    _snapshot: &mut S,
+) -> Drained {
+    let mut buf = [0u8; 8192];
+    let mut drained = Drained::Applied;
+    loop {
+        match recv(fd.as_raw_fd(), &mut buf, MsgFlags::empty()) {
+            Ok(0) => break,
+            Ok(_) => drained = drained.max(Drained::Relist),
+            Err(Errno::EAGAIN) => break,
+            Err(Errno::ENOBUFS) => drained = Drained::Overrun,
+            Err(_) => {
+                drained = drained.max(Drained::Relist);
+                break;
+            }
+        }
+    }
+    drained
+}
+
+fn route_from_message(route: RouteMessage) -> Route {
+    Route {
+        destination: route.destination,
+        prefix_len: route.prefix_len,
+        gateway: route.gateway,
+        ifindex: route.oif,
+        nexthops: route
+            .nexthops
+            .iter()
+            .map(|nexthop| NextHop {
+                gateway: nexthop.gateway,
+                ifindex: nexthop.oif,
+                weight: u16::from(nexthop.hops) + 1,
+            })
+            .collect(),
+        source: route.prefsrc,
+        metric: route.priority,
+        table: route.table,
+        protocol: route_protocol(route.protocol),
+        kind: route_type(route.kind),
+    }
+}
+
+fn rule_from_message(rule: &RuleMessage) -> Rule {
+    let network = |ip: Option<IpAddr>, prefix_len: u8| {
+        ip.filter(|_| prefix_len != 0)
+            .map(|ip| IpRecord { ip, prefix_len })
+    };
+    Rule {
+        priority: rule.priority,
+        ipv6: rule.ipv6,
+        from: network(rule.src, rule.src_len),
+        to: network(rule.dst, rule.dst_len),
+        iif: rule.iif_name.clone(),
+        oif: rule.oif_name.clone(),
+        fwmark: rule.fwmark,
+        fwmask: rule.fwmask,
+        other_selectors: rule.tos != 0 || rule.other_selectors,
+        invert: rule.flags & FIB_RULE_INVERT != 0,
+        suppress_prefixlen: rule.suppress_prefixlen,
+        action: match rule.action {
+            FR_ACT_TO_TBL => RuleAction::Table(rule.table),
+            FR_ACT_GOTO => rule.goto.map_or(RuleAction::Nop, RuleAction::Goto),
+            FR_ACT_NOP => RuleAction::Nop,
+            FR_ACT_BLACKHOLE => RuleAction::Blackhole,
+            FR_ACT_UNREACHABLE => RuleAction::Unreachable,
+            FR_ACT_PROHIBIT => RuleAction::Prohibit,
+            other => RuleAction::Other(other),
+        },
+    }
+}
+
+fn policy_rule(rule: &RuleMessage) -> PolicyRule {
+    PolicyRule {
+        ipv6: rule.ipv6,
+        priority: rule.priority,
+        matches_any: rule.matches_unbound_traffic(),
+        action: match rule.action {
+            FR_ACT_TO_TBL => PolicyAction::Table(rule.table),
+            FR_ACT_GOTO => rule.goto.map_or(PolicyAction::Nop, PolicyAction::Goto),
+            FR_ACT_BLACKHOLE | FR_ACT_UNREACHABLE | FR_ACT_PROHIBIT => PolicyAction::Reject,
+            _ => PolicyAction::Nop,
+        },
+        suppress_prefixlen: rule.suppress_prefixlen,
+    }
+}
+
+// RTPROT_* values from rtnetlink.h
+fn route_protocol(value: u8) -> RouteProtocol {
+    match value {
+        1 => RouteProtocol::Redirect,
+        2 => RouteProtocol::Kernel,
+        3 => RouteProtocol::Boot,
+        4 => RouteProtocol::Static,
+        9 => RouteProtocol::Ra,
+        16 => RouteProtocol::Dhcp,
+        other => RouteProtocol::Other(other),
+    }
+}
+
+// RTN_* values from rtnetlink.h
+fn route_type(value: u8) -> RouteType {
+    match value {
+        1 => RouteType::Unicast,
+        2 => RouteType::Local,
+        3 => RouteType::Broadcast,
+        4 => RouteType::Anycast,
+        5 => RouteType::Multicast,
+        6 => RouteType::Blackhole,
+        7 => RouteType::Unreachable,
+        8 => RouteType::Prohibit,
+        9 => RouteType::Throw,
+        other => RouteType::Other(other),
+    }
+}
@@ -0,0 +1,147 @@
+use std::net::IpAddr;
+
+use crate::{
+    Error, PrimaryPathUpdate, PrimaryPaths, Route, RouteLookup, RouteUpdate, Rule, RuleUpdate,
+    WatchOptions, WatchStats,
+};
+
+/// Route watchers cannot be created on this platform, so these types have no values.
+pub(crate) enum AsyncRouteWatch {}
+pub(crate) enum BlockingRouteWatch {}
+pub(crate) enum AsyncPrimaryPathWatch {}
+pub(crate) enum BlockingPrimaryPathWatch {}
+pub(crate) enum AsyncRuleWatch {}
+pub(crate) enum BlockingRuleWatch {}
+
+impl AsyncRouteWatch {
+    pub(crate) async fn changed(&mut self) -> RouteUpdate {
+        match *self {}
+    }
+
+    pub(crate) fn stats(&self) -> WatchStats {
+        match *self {}
+    }
+}
+
+impl BlockingRouteWatch {
+    pub(crate) fn changed(&mut self) -> RouteUpdate {
+        match *self {}
+    }
+
+    pub(crate) fn stats(&self) -> WatchStats {
+        match *self {}
+    }
+}
+
+impl AsyncPrimaryPathWatch {
+    pub(crate) async fn changed(&mut self) -> PrimaryPathUpdate {
+        match *self {}
+    }
+
+    pub(crate) fn stats(&self) -> WatchStats {
+        match *self {}
+    }
+}
+
+impl BlockingPrimaryPathWatch {
+    pub(crate) fn changed(&mut self) -> PrimaryPathUpdate {
+        match *self {}
+    }
+
+    pub(crate) fn stats(&self) -> WatchStats {
+        match *self {}
+    }
+}
+
+impl AsyncRuleWatch {
+    pub(crate) async fn changed(&mut self) -> RuleUpdate {
+        match *self {}
+    }
+
+    pub(crate) fn stats(&self) -> WatchStats {
+        match *self {}
+    }
+}
+
+impl BlockingRuleWatch {
+    pub(crate) fn changed(&mut self) -> RuleUpdate {
+        match *self {}
+    }
+
+    pub(crate) fn stats(&self) -> WatchStats {
+        match *self {}
+    }
+}
+
+/// Routes are only implemented for Linux, where they are read from rtnetlink.
+pub(crate) fn list_routes() -> Result<Vec<Route>, Error> {
+    Err(Error::Unsupported)
+}
+
+pub(crate) fn watch_routes_with_callback<F: FnMut(RouteUpdate) + Send + 'static>(
+    _callback: F,
+    _options: &WatchOptions,
+) -> Result<crate::watch::WatchHandle, Error> {
+    Err(Error::Unsupported)
+}
+
+#[allow(clippy::extra_unused_type_parameters)]
+pub(crate) fn watch_routes_async<A: crate::async_adapter::AsyncFdAdapter>(
+    _options: &WatchOptions,
+) -> Result<AsyncRouteWatch, Error> {
+    Err(Error::Unsupported)
+}
+
+pub(crate) fn watch_routes_blocking(_options: &WatchOptions) -> Result<BlockingRouteWatch, Error> {
+    Err(Error::Unsupported)
+}
+
+pub(crate) fn route_to(_destination: IpAddr) -> Result<Option<RouteLookup>, Error> {
+    Err(Error::Unsupported)
+}
+
+pub(crate) fn primary_paths() -> Result<PrimaryPaths, Error> {
+    Err(Error::Unsupported)
+}
+
+pub(crate) fn watch_primary_paths_with_callback<F: FnMut(PrimaryPathUpdate) + Send + 'static>(
+    _callback: F,
+    _options: &WatchOptions,
+) -> Result<crate::watch::WatchHandle, Error> {
+    Err(Error::Unsupported)
+}
+
+#[allow(clippy::extra_unused_type_parameters)]
+pub(crate) fn watch_primary_paths_async<A: crate::async_adapter::AsyncFdAdapter>(
+    _options: &WatchOptions,
+) -> Result<AsyncPrimaryPathWatch, Error> {
+    Err(Error::Unsupported)
+}
+
+pub(crate) fn watch_primary_paths_blocking(
+    _options: &WatchOptions,
+) -> Result<BlockingPrimaryPathWatch, Error> {
+    Err(Error::Unsupported)
+}
+
+pub(crate) fn list_rules() -> Result<Vec<Rule>, Error> {
+    Err(Error::Unsupported)
+}
+
+pub(crate) fn watch_rules_with_callback<F: FnMut(RuleUpdate) + Send + 'static>(
+    _callback: F,
+    _options: &WatchOptions,
+) -> Result<crate::watch::WatchHandle, Error> {
+    Err(Error::Unsupported)
+}
+
+#[allow(clippy::extra_unused_type_parameters)]
+pub(crate) fn watch_rules_async<A: crate::async_adapter::AsyncFdAdapter>(
+    _options: &WatchOptions,
+) -> Result<AsyncRuleWatch, Error> {
+    Err(Error::Unsupported)
+}
+
+pub(crate) fn watch_rules_blocking(_options: &WatchOptions) -> Result<BlockingRuleWatch, Error> {
+    Err(Error::Unsupported)
+}
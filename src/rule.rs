@@ -0,0 +1,209 @@
+#[cfg(any(target_os = "linux", test))]
+use crate::keyed::{Keyed, KeyedCursor, KeyedDiff, KeyedList};
+use crate::IpRecord;
+
+/// A policy routing rule, as returned by `list_rules`. Rules decide which routing table is
+/// consulted for a packet, e.g. by its source address.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Rule {
+    /// Rules are evaluated in increasing order of priority.
+    pub priority: u32,
+    /// Whether this is an IPv6 rule rather than an IPv4 one.
+    pub ipv6: bool,
+    /// The source network the rule matches, or `None` to match any source.
+    pub from: Option<IpRecord>,
+    /// The destination network the rule matches, or `None` to match any destination.
+    pub to: Option<IpRecord>,
+    /// The name of the interface traffic must arrive on. Traffic sent from this host is treated
+    /// as arriving on the loopback interface.
+    pub iif: Option<String>,
+    /// The name of the interface a socket must be bound to.
+    pub oif: Option<String>,
+    /// The firewall mark traffic must carry, after applying `fwmask`.
+    pub fwmark: Option<u32>,
+    /// The bits of the firewall mark that are compared with `fwmark`. All bits are compared if
+    /// this is `None`.
+    pub fwmask: Option<u32>,
+    /// Whether the rule also matches on something not described here, such as the TOS, a uid
+    /// range, ports or a VRF.
+    pub other_selectors: bool,
+    /// Whether the rule applies to traffic that does not match its selectors, as with
+    /// `ip rule add not ...`.
+    pub invert: bool,
+    /// Routes with a prefix no longer than this are ignored when found through the rule, so that
+    /// e.g. a value of 0 skips the table's default route.
+    pub suppress_prefixlen: Option<u32>,
+    /// What happens to traffic the rule applies to.
+    pub action: RuleAction,
+}
+
+impl Rule {
+    /// The routing table the rule looks up, if that is its action.
+    pub fn table(&self) -> Option<u32> {
+        match self.action {
+            RuleAction::Table(table) => Some(table),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", test))]
+impl Keyed for Rule {
+    type Key = Rule;
+    type Update = RuleUpdate;
+
+    /// What identifies a rule, so that a change to anything else is a modification of the same
+    /// rule. This is everything that decides which traffic the rule applies to.
+    fn key(&self) -> Rule {
+        Rule {
+            suppress_prefixlen: None,
+            action: RuleAction::Nop,
+            ..self.clone()
+        }
+    }
+
+    fn update(is_initial: bool, rules: Vec<Rule>, diff: KeyedDiff<Rule>) -> RuleUpdate {
+        RuleUpdate {
+            is_initial,
+            rules,
+            diff: RuleDiff {
+                added: diff.added,
+                removed: diff.removed,
+                modified: diff
+                    .modified
+                    .into_iter()
+                    .map(|(before, after)| ModifiedRule { before, after })
+                    .collect(),
+            },
+        }
+    }
+}
+
+/// What a rule does with the traffic it applies to, from the kernel's `FR_ACT_*` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RuleAction {
+    /// Look up a route in the given table, e.g. `Route::MAIN_TABLE`. If no route is found,
+    /// evaluation continues with the next rule.
+    Table(u32),
+    /// Continue evaluation at the rule with the given priority.
+    Goto(u32),
+    /// Do nothing and continue with the next rule.
+    Nop,
+    /// Silently discard the traffic.
+    Blackhole,
+    /// Discard the traffic with a "network unreachable" error.
+    Unreachable,
+    /// Discard the traffic with an "administratively prohibited" error.
+    Prohibit,
+    /// An action value with no dedicated variant.
+    Other(u8),
+}
+
+/// Information delivered when the policy routing rules change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleUpdate {
+    /// Whether this update represents the initial existing rules.
+    pub is_initial: bool,
+    /// Every current rule, sorted by priority.
+    pub rules: Vec<Rule>,
+    /// The changes from the preceding update to `rules`.
+    pub diff: RuleDiff,
+}
+
+/// What changed between one `RuleUpdate` and the next.
+///
+/// For an initial update, every current rule is listed as added.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RuleDiff {
+    /// Rules that appeared.
+    pub added: Vec<Rule>,
+    /// Rules that disappeared, containing their last known state.
+    pub removed: Vec<Rule>,
+    /// Rules whose priority and selectors stayed the same but whose action changed, e.g. to look
+    /// up a different table.
+    pub modified: Vec<ModifiedRule>,
+}
+
+/// The previous and current state of a rule that was modified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModifiedRule {
+    pub before: Rule,
+    pub after: Rule,
+}
+
+/// A snapshot of every rule, kept sorted so that equal rule sets compare equal.
+#[cfg(any(target_os = "linux", test))]
+pub(crate) type RuleList = KeyedList<Rule>;
+
+#[cfg(any(target_os = "linux", test))]
+pub(crate) type RuleCursor = KeyedCursor<Rule>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn rule(priority: u32, from_octet: u8, action: RuleAction) -> Rule {
+        Rule {
+            priority,
+            ipv6: false,
+            from: Some(IpRecord {
+                ip: IpAddr::V4(Ipv4Addr::new(192, 0, 2, from_octet)),
+                prefix_len: 32,
+            }),
+            to: None,
+            iif: None,
+            oif: None,
+            fwmark: None,
+            fwmask: None,
+            other_selectors: false,
+            invert: false,
+            suppress_prefixlen: None,
+            action,
+        }
+    }
+
+    #[test]
+    fn changed_table_is_a_modification() {
+        let mut cursor = RuleCursor::default();
+        let initial = cursor
+            .advance(RuleList::new(vec![
+                rule(200, 2, RuleAction::Table(101)),
+                rule(100, 1, RuleAction::Table(100)),
+            ]))
+            .unwrap();
+        assert!(initial.is_initial);
+        assert_eq!(
+            initial.rules,
+            vec![
+                rule(100, 1, RuleAction::Table(100)),
+                rule(200, 2, RuleAction::Table(101)),
+            ]
+        );
+        assert_eq!(initial.diff.added, initial.rules);
+
+        // Two rules at one priority are distinct when their selectors differ.
+        let next = RuleList::new(vec![
+            rule(100, 1, RuleAction::Table(100)),
+            rule(200, 2, RuleAction::Table(102)),
+            rule(200, 3, RuleAction::Table(102)),
+        ]);
+        let update = cursor.advance(next.clone()).unwrap();
+
+        assert!(!update.is_initial);
+        assert_eq!(
+            update.diff.added,
+            vec![rule(200, 3, RuleAction::Table(102))]
+        );
+        assert!(update.diff.removed.is_empty());
+        assert_eq!(
+            update.diff.modified,
+            vec![ModifiedRule {
+                before: rule(200, 2, RuleAction::Table(101)),
+                after: rule(200, 2, RuleAction::Table(102)),
+            }]
+        );
+        assert_eq!(update.diff.modified[0].after.table(), Some(102));
+        assert_eq!(cursor.advance(next), None);
+    }
+}
@@ -0,0 +1,129 @@
+use std::time::Instant;
+
+/// Traffic counters for one interface, sampled with `interface_stats`.
+///
+/// Counters are cumulative since the interface was created or its driver last reset them. Use
+/// `rates_since` to turn two samples into rates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InterfaceStats {
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    pub rx_dropped: u64,
+    pub tx_dropped: u64,
+    /// When the counters were read.
+    pub sampled_at: Instant,
+}
+
+/// Per-second rates between two `InterfaceStats` samples.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct InterfaceRates {
+    pub rx_bytes: f64,
+    pub tx_bytes: f64,
+    pub rx_packets: f64,
+    pub tx_packets: f64,
+    pub rx_errors: f64,
+    pub tx_errors: f64,
+    pub rx_dropped: f64,
+    pub tx_dropped: f64,
+    /// Whether any counter went backwards between the samples, e.g. because the interface was
+    /// re-created with the same index. Such a counter is assumed to have restarted from zero, so
+    /// its rate is based on its current value alone.
+    pub counters_reset: bool,
+}
+
+impl InterfaceStats {
+    /// Compute rates from an `earlier` sample of the same interface to this one.
+    ///
+    /// Returns `None` if this sample was not taken after `earlier`.
+    pub fn rates_since(&self, earlier: &InterfaceStats) -> Option<InterfaceRates> {
+        let elapsed = self
+            .sampled_at
+            .checked_duration_since(earlier.sampled_at)?
+            .as_secs_f64();
+        if elapsed == 0.0 {
+            return None;
+        }
+        let mut counters_reset = false;
+        let mut rate = |now: u64, before: u64| {
+            let delta = match now.checked_sub(before) {
+                Some(delta) => delta,
+                None => {
+                    counters_reset = true;
+                    now
+                }
+            };
+            delta as f64 / elapsed
+        };
+        Some(InterfaceRates {
+            rx_bytes: rate(self.rx_bytes, earlier.rx_bytes),
+            tx_bytes: rate(self.tx_bytes, earlier.tx_bytes),
+            rx_packets: rate(self.rx_packets, earlier.rx_packets),
+            tx_packets: rate(self.tx_packets, earlier.tx_packets),
+            rx_errors: rate(self.rx_errors, earlier.rx_errors),
+            tx_errors: rate(self.tx_errors, earlier.tx_errors),
+            rx_dropped: rate(self.rx_dropped, earlier.rx_dropped),
+            tx_dropped: rate(self.tx_dropped, earlier.tx_dropped),
+            counters_reset,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn sample(rx_bytes: u64, tx_bytes: u64, sampled_at: Instant) -> InterfaceStats {
+        InterfaceStats {
+            rx_bytes,
+            tx_bytes,
+            rx_packets: 0,
+            tx_packets: 0,
+            rx_errors: 0,
+            tx_errors: 0,
+            rx_dropped: 0,
+            tx_dropped: 0,
+            sampled_at,
+        }
+    }
+
+    #[test]
+    fn rates_are_per_second() {
+        let start = Instant::now();
+        let earlier = sample(1000, 500, start);
+        let later = sample(3000, 1500, start + Duration::from_secs(2));
+
+        let rates = later.rates_since(&earlier).unwrap();
+
+        assert_eq!(rates.rx_bytes, 1000.0);
+        assert_eq!(rates.tx_bytes, 500.0);
+        assert!(!rates.counters_reset);
+    }
+
+    #[test]
+    fn a_counter_that_goes_backwards_restarted_from_zero() {
+        let start = Instant::now();
+        let earlier = sample(1_000_000, 500, start);
+        let later = sample(400, 700, start + Duration::from_secs(2));
+
+        let rates = later.rates_since(&earlier).unwrap();
+
+        assert_eq!(rates.rx_bytes, 200.0);
+        assert_eq!(rates.tx_bytes, 100.0);
+        assert!(rates.counters_reset);
+    }
+
+    #[test]
+    fn samples_out_of_order_have_no_rate() {
+        let start = Instant::now();
+        let earlier = sample(0, 0, start);
+        let later = sample(0, 0, start + Duration::from_secs(1));
+
+        assert_eq!(earlier.rates_since(&later), None);
+        assert_eq!(earlier.rates_since(&earlier), None);
+    }
+}
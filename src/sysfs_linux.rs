@@ -0,0 +1,158 @@
+//! Link speed and driver details, read from sysfs and the ethtool ioctl.
+
+use std::fs;
+use std::os::fd::{AsRawFd, BorrowedFd};
+use std::path::{Path, PathBuf};
+
+use nix::libc;
+
+use crate::{Duplex, LinkDetails};
+
+const SYSFS_NET: &str = "/sys/class/net";
+
+const ETHTOOL_GSET: u32 = 0x1;
+const AUTONEG_ENABLE: u8 = 0x1;
+
+/// `struct ethtool_cmd` from ethtool.h.
+#[repr(C)]
+#[derive(Default)]
+struct EthtoolCmd {
+    cmd: u32,
+    supported: u32,
+    advertising: u32,
+    speed: u16,
+    duplex: u8,
+    port: u8,
+    phy_address: u8,
+    transceiver: u8,
+    autoneg: u8,
+    mdio_support: u8,
+    maxtxpkt: u32,
+    maxrxpkt: u32,
+    speed_hi: u16,
+    eth_tp_mdix: u8,
+    eth_tp_mdix_ctrl: u8,
+    lp_advertising: u32,
+    reserved: [u32; 2],
+}
+
+/// Describe the interface called `name`, using `socket` for ethtool requests.
+pub(crate) fn link_details(socket: Option<BorrowedFd<'_>>, name: &str) -> LinkDetails {
+    let mut details = read_sysfs(&Path::new(SYSFS_NET).join(name));
+    if details.driver.is_some() {
+        details.autoneg = socket.and_then(|socket| ethtool_autoneg(socket, name));
+    }
+    details
+}
+
+fn read_sysfs(dir: &Path) -> LinkDetails {
+    // Virtual devices such as veth report a made-up speed, so only interfaces backed by a device
+    // are described.
+    let Ok(device) = fs::read_link(dir.join("device")) else {
+        return LinkDetails::default();
+    };
+    LinkDetails {
+        // An unknown speed reads as -1, and reading fails entirely while the link is down.
+        speed_mbps: read_value(&dir.join("speed"))
+            .and_then(|speed| speed.parse::<u32>().ok())
+            .filter(|&speed| speed != 0 && speed != u32::MAX),
+        duplex: match read_value(&dir.join("duplex")).as_deref() {
+            Some("full") => Some(Duplex::Full),
+            Some("half") => Some(Duplex::Half),
+            _ => None,
+        },
+        autoneg: None,
+        driver: fs::read_link(dir.join("device/driver"))
+            .ok()
+            .and_then(file_name),
+        bus_info: file_name(device),
+    }
+}
+
+fn read_value(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|value| value.trim().to_string())
+}
+
+fn file_name(path: PathBuf) -> Option<String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+fn ethtool_autoneg(socket: BorrowedFd<'_>, name: &str) -> Option<bool> {
+    let mut req = crate::ifreq::ifreq_for(name)?;
+    let mut cmd = EthtoolCmd {
+        cmd: ETHTOOL_GSET,
+        ..Default::default()
+    };
+    req.ifr_ifru.ifru_data = (&mut cmd as *mut EthtoolCmd).cast();
+    let res = unsafe { libc::ioctl(socket.as_raw_fd(), libc::SIOCETHTOOL as _, &mut req) };
+    (res >= 0).then_some(cmd.autoneg == AUTONEG_ENABLE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("netwatcher-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn hardware_link_is_described() {
+        let dir = TempDir::new("sysfs-nic");
+        let device = dir.0.join("0000:00:1f.6");
+        fs::create_dir_all(device.join("e1000e")).unwrap();
+        symlink(device.join("e1000e"), device.join("driver")).unwrap();
+        let iface = dir.0.join("eth0");
+        fs::create_dir(&iface).unwrap();
+        symlink(&device, iface.join("device")).unwrap();
+        fs::write(iface.join("speed"), "1000\n").unwrap();
+        fs::write(iface.join("duplex"), "full\n").unwrap();
+
+        let details = read_sysfs(&iface);
+
+        assert_eq!(details.speed_mbps, Some(1000));
+        assert_eq!(details.duplex, Some(Duplex::Full));
+        assert_eq!(details.driver.as_deref(), Some("e1000e"));
+        assert_eq!(details.bus_info.as_deref(), Some("0000:00:1f.6"));
+    }
+
+    #[test]
+    fn unknown_speed_and_virtual_links_are_not_described() {
+        let dir = TempDir::new("sysfs-virtual");
+        let device = dir.0.join("virtio0");
+        fs::create_dir(&device).unwrap();
+        let iface = dir.0.join("eth0");
+        fs::create_dir(&iface).unwrap();
+        symlink(&device, iface.join("device")).unwrap();
+        fs::write(iface.join("speed"), "-1\n").unwrap();
+        fs::write(iface.join("duplex"), "unknown\n").unwrap();
+
+        let details = read_sysfs(&iface);
+        assert_eq!(details.speed_mbps, None);
+        assert_eq!(details.duplex, None);
+        assert_eq!(details.driver, None);
+
+        let veth = dir.0.join("veth0");
+        fs::create_dir(&veth).unwrap();
+        fs::write(veth.join("speed"), "10000\n").unwrap();
+        assert_eq!(read_sysfs(&veth), LinkDetails::default());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{list, Error, List, Update};
+use crate::{list, Error, List, Update, WatchOptions, WatchStats};
 use jni::objects::{Global, JClass, JObject, JString};
 use jni::{jni_sig, jni_str, Env, EnvUnowned, NativeMethod};
 use std::collections::{HashMap, HashSet};
@@ -20,7 +20,7 @@ static STATE: OnceLock<Arc<Mutex<State>>> = OnceLock::new();
 type WatcherId = usize;
 
 struct State {
-    callback_watchers: HashMap<WatcherId, Callback>,
+    callback_watchers: HashMap<WatcherId, CallbackWatcher>,
     initialising_callback_watchers: HashSet<WatcherId>,
     queued_watchers: HashMap<WatcherId, SharedAsyncCallbackQueue>,
     current_interfaces: List,
@@ -36,6 +36,12 @@ impl State {
     }
 }
 
+/// A registered callback and the snapshot filter it was created with.
+struct CallbackWatcher {
+    callback: Callback,
+    include_down: bool,
+}
+
 struct InitialisingCallbackWatcher {
     id: WatcherId,
     committed: bool,
@@ -93,6 +99,10 @@ impl AsyncWatch {
             }
         }
     }
+
+    pub(crate) fn stats(&self) -> WatchStats {
+        WatchStats::default()
+    }
 }
 
 impl Drop for BlockingWatch {
@@ -110,6 +120,16 @@ impl BlockingWatch {
             }
         }
     }
+
+    pub(crate) fn stats(&self) -> WatchStats {
+        WatchStats::default()
+    }
+}
+
+impl WatchHandle {
+    pub(crate) fn stats(&self) -> WatchStats {
+        WatchStats::default()
+    }
 }
 
 impl Drop for WatchHandle {
@@ -120,38 +140,44 @@ impl Drop for WatchHandle {
 
 pub(crate) fn watch_interfaces_with_callback<F: FnMut(Update) + Send + 'static>(
     callback: F,
+    options: &WatchOptions,
 ) -> Result<WatchHandle, Error> {
-    let id = register_callback_watcher(Box::new(callback))?;
+    let id = register_callback_watcher(Box::new(callback), options.include_down)?;
     Ok(WatchHandle { id })
 }
 
 #[allow(clippy::extra_unused_type_parameters)]
 pub(crate) fn watch_interfaces_async<A: crate::async_adapter::AsyncFdAdapter>(
+    options: &WatchOptions,
 ) -> Result<AsyncWatch, Error> {
     let queue = shared_async_callback_queue();
     let id = register_queued_watcher(queue.clone())?;
     Ok(AsyncWatch {
         id,
         queue,
-        cursor: crate::UpdateCursor::default(),
+        cursor: crate::UpdateCursor::new(options),
     })
 }
 
-pub(crate) fn watch_interfaces_blocking() -> Result<BlockingWatch, Error> {
+pub(crate) fn watch_interfaces_blocking(options: &WatchOptions) -> Result<BlockingWatch, Error> {
     let queue = shared_async_callback_queue();
     let id = register_queued_watcher(queue.clone())?;
     Ok(BlockingWatch {
         id,
         queue,
-        cursor: crate::UpdateCursor::default(),
+        cursor: crate::UpdateCursor::new(options),
     })
 }
 
 fn register_callback_watcher(
     callback: Box<dyn FnMut(Update) + Send + 'static>,
+    include_down: bool,
 ) -> Result<WatcherId, Error> {
     let state_ref = STATE.get_or_init(init_state).clone();
-    let mut callback = Callback::new(callback);
+    let mut watcher = CallbackWatcher {
+        callback: Callback::new(callback),
+        include_down,
+    };
 
     let (id, initial_list) = {
         let mut state = state_ref.lock().unwrap();
@@ -163,8 +189,10 @@ fn register_callback_watcher(
     };
     let mut registration = InitialisingCallbackWatcher::new(id);
 
-    callback.call_initial(initial_list.initial_update());
-    finish_callback_registration(&state_ref, id, initial_list, callback);
+    watcher
+        .callback
+        .call_initial(initial_list.clone().visible(include_down).initial_update());
+    finish_callback_registration(&state_ref, id, initial_list, watcher);
     registration.commit();
     Ok(id)
 }
@@ -173,22 +201,26 @@ fn finish_callback_registration(
     state_ref: &Arc<Mutex<State>>,
     id: WatcherId,
     mut current_list: List,
-    mut callback: Callback,
+    mut watcher: CallbackWatcher,
 ) {
     loop {
         let next_list = {
             let mut state = state_ref.lock().unwrap();
             if state.current_interfaces == current_list {
                 state.initialising_callback_watchers.remove(&id);
-                state.callback_watchers.insert(id, callback);
+                state.callback_watchers.insert(id, watcher);
                 return;
             }
             state.current_interfaces.clone()
         };
 
-        let update = next_list.update_from(&current_list);
+        let prev_visible = current_list.visible(watcher.include_down);
+        let next_visible = next_list.clone().visible(watcher.include_down);
         current_list = next_list;
-        callback.call_from_notification(update);
+        if next_visible != prev_visible {
+            let update = next_visible.update_from(&prev_visible);
+            watcher.callback.call_from_notification(update);
+        }
     }
 }
 
@@ -391,12 +423,26 @@ fn interfaces_did_change() {
         return;
     }
 
-    let update = (!state.callback_watchers.is_empty())
-        .then(|| new_list.update_from(&state.current_interfaces));
-    state.current_interfaces = new_list;
-
-    if let Some(update) = update {
-        dispatch_callbacks(state.callback_watchers.values_mut(), update);
+    let state = &mut *state;
+    let prev_list = std::mem::replace(&mut state.current_interfaces, new_list);
+
+    // Callback watchers share one snapshot but may filter it differently, so compute the update
+    // once for each filter that is in use.
+    for include_down in [false, true] {
+        let mut callbacks = state
+            .callback_watchers
+            .values_mut()
+            .filter(|watcher| watcher.include_down == include_down)
+            .map(|watcher| &mut watcher.callback)
+            .peekable();
+        if callbacks.peek().is_none() {
+            continue;
+        }
+        let prev_visible = prev_list.clone().visible(include_down);
+        let new_visible = state.current_interfaces.clone().visible(include_down);
+        if new_visible != prev_visible {
+            dispatch_callbacks(callbacks, new_visible.update_from(&prev_visible));
+        }
     }
     for queue in state.queued_watchers.values() {
         push_async_list(queue, state.current_interfaces.clone());
@@ -1,42 +1,152 @@
+#[cfg(target_os = "linux")]
+use std::os::fd::FromRawFd;
 use std::os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd};
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
 
+#[cfg(target_os = "linux")]
+use nix::libc::{epoll_create1, epoll_ctl, epoll_event, EPOLLIN, EPOLL_CLOEXEC, EPOLL_CTL_ADD};
 use nix::libc::{poll, pollfd, POLLIN};
 use nix::unistd::pipe;
 
-use crate::{Error, Update};
+use crate::{Error, List, Update, UpdateCursor, WatchOptions, WatchStats};
 
-pub(crate) type DrainEventSocket = for<'fd> fn(BorrowedFd<'fd>);
-pub(crate) type OpenEventSocket = fn() -> Result<OwnedFd, Error>;
+/// What a drain function did with the notifications it read from the event socket.
+///
+/// Variants are ordered by severity so that a drain can keep the worst outcome with `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Drained {
+    /// Every notification was applied to the tracked list in place.
+    #[cfg_attr(not(any(target_os = "linux", target_os = "android")), allow(dead_code))]
+    Applied,
+    /// The notifications cannot be applied incrementally, so the list must be fetched again.
+    Relist,
+    /// The kernel dropped notifications, so the list must be fetched again.
+    Overrun,
+}
+
+/// State that a watcher keeps up to date from event socket notifications, and the cursor that
+/// turns successive states into updates for the caller.
+pub(crate) trait Snapshot: Clone + Send + 'static {
+    type Update: Send + 'static;
+    type Cursor: Send + 'static;
+
+    /// Fetch the complete current state.
+    fn fetch(options: &WatchOptions) -> Result<Self, Error>;
+
+    fn cursor(options: &WatchOptions) -> Self::Cursor;
+
+    /// Produce an update if `snapshot` differs from what the cursor last reported.
+    fn advance(cursor: &mut Self::Cursor, snapshot: Self) -> Option<Self::Update>;
+}
+
+impl Snapshot for List {
+    type Update = Update;
+    type Cursor = UpdateCursor;
+
+    fn fetch(_options: &WatchOptions) -> Result<Self, Error> {
+        crate::list::list_interfaces()
+    }
+
+    fn cursor(options: &WatchOptions) -> UpdateCursor {
+        UpdateCursor::new(options)
+    }
+
+    fn advance(cursor: &mut UpdateCursor, list: List) -> Option<Update> {
+        cursor.advance(list)
+    }
+}
+
+/// Drains the socket a watcher polls. For a multiplexed socket, the descriptors it combines are
+/// passed as well, since those are what hold the notifications.
+pub(crate) type DrainEventSocket<S> = for<'fd> fn(BorrowedFd<'fd>, &[OwnedFd], &mut S) -> Drained;
+pub(crate) type OpenEventSocket = fn(&WatchOptions) -> Result<EventSocket, Error>;
+
+/// The descriptor a watcher polls for notifications, along with any descriptors that it
+/// multiplexes and which must stay open as long as it does.
+pub(crate) struct EventSocket {
+    fd: OwnedFd,
+    sources: Vec<OwnedFd>,
+}
+
+impl From<OwnedFd> for EventSocket {
+    fn from(fd: OwnedFd) -> Self {
+        Self {
+            fd,
+            sources: Vec::new(),
+        }
+    }
+}
+
+impl EventSocket {
+    /// Combine several descriptors behind an epoll descriptor that polls readable whenever any
+    /// of them does.
+    #[cfg(target_os = "linux")]
+    pub(crate) fn multiplex(sources: Vec<OwnedFd>) -> Result<Self, Error> {
+        let fd = unsafe { epoll_create1(EPOLL_CLOEXEC) };
+        if fd == -1 {
+            return Err(Error::Io(std::io::Error::last_os_error()));
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        for source in &sources {
+            let mut event = epoll_event {
+                events: EPOLLIN as u32,
+                u64: 0,
+            };
+            if unsafe {
+                epoll_ctl(
+                    fd.as_raw_fd(),
+                    EPOLL_CTL_ADD,
+                    source.as_raw_fd(),
+                    &mut event,
+                )
+            } == -1
+            {
+                return Err(Error::Io(std::io::Error::last_os_error()));
+            }
+        }
+        Ok(Self { fd, sources })
+    }
+}
 
-#[derive(Clone, Copy)]
-pub(crate) struct EventSocketOps {
+pub(crate) struct EventSocketOps<S> {
     pub(crate) open: OpenEventSocket,
-    pub(crate) drain: DrainEventSocket,
+    pub(crate) drain: DrainEventSocket<S>,
 }
 
+// Derived impls would require `S: Copy`.
+impl<S> Clone for EventSocketOps<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for EventSocketOps<S> {}
+
 pub(crate) struct WatchHandle {
     pipefd: Option<OwnedFd>,
     complete: Option<mpsc::Receiver<()>>,
+    counters: Arc<Counters>,
 }
 
-pub(crate) struct AsyncWatch {
+pub(crate) struct AsyncWatch<S: Snapshot = List> {
     registration: Box<dyn crate::async_adapter::AsyncFdRegistration>,
-    cursor: crate::UpdateCursor,
-    initial_update: Option<Update>,
-    drain_event_socket: DrainEventSocket,
+    sources: Vec<OwnedFd>,
+    tracker: Tracker<S>,
+    initial_update: Option<S::Update>,
+    drain_event_socket: DrainEventSocket<S>,
     readiness_failed: bool,
 }
 
-pub(crate) struct BlockingWatch {
-    socket: OwnedFd,
-    cursor: crate::UpdateCursor,
-    initial_update: Option<Update>,
-    drain_event_socket: DrainEventSocket,
+pub(crate) struct BlockingWatch<S: Snapshot = List> {
+    socket: EventSocket,
+    tracker: Tracker<S>,
+    initial_update: Option<S::Update>,
+    drain_event_socket: DrainEventSocket<S>,
 }
 
-impl AsyncWatch {
-    pub(crate) async fn changed(&mut self) -> Update {
+impl<S: Snapshot> AsyncWatch<S> {
+    pub(crate) async fn changed(&mut self) -> S::Update {
         if let Some(initial_update) = self.initial_update.take() {
             return initial_update;
         }
@@ -54,36 +164,51 @@ impl AsyncWatch {
                 }
             };
 
-            (self.drain_event_socket)(ready.fd().as_fd());
+            let update = self.tracker.next_update(
+                ready.fd().as_fd(),
+                &self.sources,
+                self.drain_event_socket,
+            );
             ready.clear_ready();
 
-            let Ok(new_list) = crate::list::list_interfaces() else {
-                continue;
-            };
-            if let Some(update) = self.cursor.advance(new_list) {
+            if let Some(update) = update {
                 return update;
             }
         }
     }
+
+    pub(crate) fn stats(&self) -> WatchStats {
+        self.tracker.counters.stats()
+    }
 }
 
-impl BlockingWatch {
-    pub(crate) fn changed(&mut self) -> Update {
+impl<S: Snapshot> BlockingWatch<S> {
+    pub(crate) fn changed(&mut self) -> S::Update {
         if let Some(initial_update) = self.initial_update.take() {
             return initial_update;
         }
 
         loop {
-            wait_for_readable(self.socket.as_fd());
-            if let Some(update) = next_update(
-                &mut self.cursor,
-                self.socket.as_fd(),
+            wait_for_readable(self.socket.fd.as_fd());
+            if let Some(update) = self.tracker.next_update(
+                self.socket.fd.as_fd(),
+                &self.socket.sources,
                 self.drain_event_socket,
             ) {
                 return update;
             }
         }
     }
+
+    pub(crate) fn stats(&self) -> WatchStats {
+        self.tracker.counters.stats()
+    }
+}
+
+impl WatchHandle {
+    pub(crate) fn stats(&self) -> WatchStats {
+        self.counters.stats()
+    }
 }
 
 impl Drop for WatchHandle {
@@ -93,57 +218,62 @@ impl Drop for WatchHandle {
     }
 }
 
-pub(crate) fn watch_interfaces_with_callback<F: FnMut(Update) + Send + 'static>(
+pub(crate) fn watch_with_callback<S: Snapshot, F: FnMut(S::Update) + Send + 'static>(
     callback: F,
-    ops: EventSocketOps,
+    ops: EventSocketOps<S>,
+    options: &WatchOptions,
 ) -> Result<WatchHandle, Error> {
-    let (pipefd, complete) = start_watcher_thread(callback, ops)?;
+    let (pipefd, complete, counters) = start_watcher_thread(callback, ops, options)?;
     Ok(WatchHandle {
         pipefd: Some(pipefd),
         complete: Some(complete),
+        counters,
     })
 }
 
-pub(crate) fn watch_interfaces_async<A: crate::async_adapter::AsyncFdAdapter>(
-    ops: EventSocketOps,
-) -> Result<AsyncWatch, Error> {
-    let socket = (ops.open)()?;
-    let registration = A::register(crate::async_adapter::AsyncFd::from_owned_fd(socket))
+pub(crate) fn watch_async<S: Snapshot, A: crate::async_adapter::AsyncFdAdapter>(
+    ops: EventSocketOps<S>,
+    options: &WatchOptions,
+) -> Result<AsyncWatch<S>, Error> {
+    let socket = (ops.open)(options)?;
+    let registration = A::register(crate::async_adapter::AsyncFd::from_owned_fd(socket.fd))
         .map_err(crate::Error::Io)?;
-    let current_list = crate::list::list_interfaces()?;
-    let mut cursor = crate::UpdateCursor::default();
-    let initial_update = cursor.advance(current_list);
+    let (tracker, initial_update) = Tracker::new(options)?;
     Ok(AsyncWatch {
         registration,
-        cursor,
-        initial_update,
+        sources: socket.sources,
+        tracker,
+        initial_update: Some(initial_update),
         drain_event_socket: ops.drain,
         readiness_failed: false,
     })
 }
 
-pub(crate) fn watch_interfaces_blocking(ops: EventSocketOps) -> Result<BlockingWatch, Error> {
-    let socket = (ops.open)()?;
-    let current_list = crate::list::list_interfaces()?;
-    let mut cursor = crate::UpdateCursor::default();
-    let initial_update = cursor.advance(current_list);
+pub(crate) fn watch_blocking<S: Snapshot>(
+    ops: EventSocketOps<S>,
+    options: &WatchOptions,
+) -> Result<BlockingWatch<S>, Error> {
+    let socket = (ops.open)(options)?;
+    let (tracker, initial_update) = Tracker::new(options)?;
     Ok(BlockingWatch {
         socket,
-        cursor,
-        initial_update,
+        tracker,
+        initial_update: Some(initial_update),
         drain_event_socket: ops.drain,
     })
 }
 
-fn start_watcher_thread<F: FnMut(Update) + Send + 'static>(
+fn start_watcher_thread<S: Snapshot, F: FnMut(S::Update) + Send + 'static>(
     mut callback: F,
-    ops: EventSocketOps,
-) -> Result<(OwnedFd, mpsc::Receiver<()>), Error> {
-    let sockfd = (ops.open)()?;
+    ops: EventSocketOps<S>,
+    options: &WatchOptions,
+) -> Result<(OwnedFd, mpsc::Receiver<()>, Arc<Counters>), Error> {
+    let socket = (ops.open)(options)?;
     let (pipe_rd, pipe_wr) = pipe().map_err(|e| Error::CreatePipe(e.to_string()))?;
-    let mut cursor = crate::UpdateCursor::default();
+    let (mut tracker, initial_update) = Tracker::<S>::new(options)?;
+    let counters = tracker.counters.clone();
 
-    (callback)(cursor.advance(crate::list::list_interfaces()?).unwrap());
+    (callback)(initial_update);
 
     let (complete_tx, complete_rx) = mpsc::channel();
 
@@ -151,7 +281,7 @@ fn start_watcher_thread<F: FnMut(Update) + Send + 'static>(
         loop {
             let mut fds = [
                 pollfd {
-                    fd: sockfd.as_raw_fd(),
+                    fd: socket.fd.as_raw_fd(),
                     events: POLLIN,
                     revents: 0,
                 },
@@ -165,7 +295,9 @@ fn start_watcher_thread<F: FnMut(Update) + Send + 'static>(
                 poll(&mut fds as *mut _, 2, -1);
             }
             if fds[0].revents != 0 {
-                if let Some(update) = next_update(&mut cursor, sockfd.as_fd(), ops.drain) {
+                if let Some(update) =
+                    tracker.next_update(socket.fd.as_fd(), &socket.sources, ops.drain)
+                {
                     (callback)(update);
                 }
             }
@@ -177,19 +309,74 @@ fn start_watcher_thread<F: FnMut(Update) + Send + 'static>(
         drop(complete_tx);
     });
 
-    Ok((pipe_wr, complete_rx))
+    Ok((pipe_wr, complete_rx, counters))
+}
+
+/// Watcher statistics, shared with the handle when the tracker runs on a background thread.
+#[derive(Default)]
+struct Counters {
+    overruns: AtomicU64,
+    resyncs: AtomicU64,
+}
+
+impl Counters {
+    fn stats(&self) -> WatchStats {
+        WatchStats {
+            overruns: self.overruns.load(Ordering::Relaxed),
+            resyncs: self.resyncs.load(Ordering::Relaxed),
+        }
+    }
 }
 
-fn next_update(
-    cursor: &mut crate::UpdateCursor,
-    fd: BorrowedFd<'_>,
-    drain_event_socket: DrainEventSocket,
-) -> Option<Update> {
-    drain_event_socket(fd);
-    let Ok(new_list) = crate::list::list_interfaces() else {
-        return None;
-    };
-    cursor.advance(new_list)
+/// The snapshot maintained from socket notifications, and the cursor that turns it into
+/// updates. An interface list includes interfaces that are down, which the cursor filters out
+/// according to the watch options.
+struct Tracker<S: Snapshot> {
+    options: WatchOptions,
+    snapshot: S,
+    cursor: S::Cursor,
+    relist_pending: bool,
+    counters: Arc<Counters>,
+}
+
+impl<S: Snapshot> Tracker<S> {
+    fn new(options: &WatchOptions) -> Result<(Self, S::Update), Error> {
+        let snapshot = S::fetch(options)?;
+        let mut cursor = S::cursor(options);
+        let initial_update = S::advance(&mut cursor, snapshot.clone()).unwrap();
+        let tracker = Self {
+            options: options.clone(),
+            snapshot,
+            cursor,
+            relist_pending: false,
+            counters: Arc::default(),
+        };
+        Ok((tracker, initial_update))
+    }
+
+    fn next_update(
+        &mut self,
+        fd: BorrowedFd<'_>,
+        sources: &[OwnedFd],
+        drain_event_socket: DrainEventSocket<S>,
+    ) -> Option<S::Update> {
+        match drain_event_socket(fd, sources, &mut self.snapshot) {
+            Drained::Applied => {}
+            Drained::Relist => self.relist_pending = true,
+            Drained::Overrun => {
+                self.counters.overruns.fetch_add(1, Ordering::Relaxed);
+                self.relist_pending = true;
+            }
+        }
+        // A failed relist stays pending so that later notifications are not applied to a
+        // snapshot that is already known to be stale.
+        if self.relist_pending {
+            self.snapshot = S::fetch(&self.options).ok()?;
+            self.relist_pending = false;
+            self.counters.resyncs.fetch_add(1, Ordering::Relaxed);
+        }
+        S::advance(&mut self.cursor, self.snapshot.clone())
+    }
 }
 
 fn wait_for_readable(fd: BorrowedFd<'_>) {
@@ -207,3 +394,33 @@ fn wait_for_readable(fd: BorrowedFd<'_>) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overrun(_fd: BorrowedFd<'_>, _sources: &[OwnedFd], list: &mut List) -> Drained {
+        list.0.clear();
+        Drained::Overrun
+    }
+
+    #[test]
+    fn overrun_forces_a_counted_relist() {
+        let (pipe_rd, _pipe_wr) = pipe().unwrap();
+        let (mut tracker, initial_update) = Tracker::<List>::new(&WatchOptions::default()).unwrap();
+
+        // The drain emptied the list, but the relist restores it so nothing changes.
+        assert_eq!(tracker.next_update(pipe_rd.as_fd(), &[], overrun), None);
+        assert_eq!(
+            tracker.snapshot.clone().visible(false).0,
+            initial_update.interfaces
+        );
+        assert_eq!(
+            tracker.counters.stats(),
+            WatchStats {
+                overruns: 1,
+                resyncs: 1,
+            }
+        );
+    }
+}
@@ -1,9 +1,11 @@
-use std::os::fd::{AsRawFd, BorrowedFd, OwnedFd};
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd};
 
 use nix::errno::Errno;
 use nix::sys::socket::bind;
 use nix::sys::socket::recv;
+use nix::sys::socket::setsockopt;
 use nix::sys::socket::socket;
+use nix::sys::socket::sockopt::{RcvBuf, RcvBufForce};
 use nix::sys::socket::AddressFamily;
 use nix::sys::socket::MsgFlags;
 use nix::sys::socket::NetlinkAddr;
@@ -11,35 +13,116 @@ use nix::sys::socket::SockFlag;
 use nix::sys::socket::SockProtocol;
 use nix::sys::socket::SockType;
 
-pub(crate) use crate::watch_fd::{AsyncWatch, BlockingWatch, WatchHandle};
+use crate::netlink::messages;
+pub(crate) use crate::watch_fd::WatchHandle;
+use crate::watch_fd::{Drained, EventSocket, Snapshot};
+use crate::DnsConfig;
+use crate::DnsOptions;
 use crate::Error;
+use crate::List;
 use crate::Update;
+use crate::UpdateCursor;
+use crate::WatchOptions;
 
-const EVENT_SOCKET_OPS: crate::watch_fd::EventSocketOps = crate::watch_fd::EventSocketOps {
-    open: open_event_socket,
-    drain: drain_event_socket,
-};
+pub(crate) type AsyncWatch = crate::watch_fd::AsyncWatch<InterfaceSnapshot>;
+pub(crate) type BlockingWatch = crate::watch_fd::BlockingWatch<InterfaceSnapshot>;
 
-const RTMGRP_IPV4_IFADDR: u32 = 0x10;
-const RTMGRP_IPV6_IFADDR: u32 = 0x100;
-const RTMGRP_LINK: u32 = 0x01;
+const EVENT_SOCKET_OPS: crate::watch_fd::EventSocketOps<InterfaceSnapshot> =
+    crate::watch_fd::EventSocketOps {
+        open: open_event_socket,
+        drain: drain_event_socket,
+    };
+
+pub(crate) const RTMGRP_LINK: u32 = 0x01;
+pub(crate) const RTMGRP_NEIGH: u32 = 0x04;
+pub(crate) const RTMGRP_IPV4_IFADDR: u32 = 0x10;
+pub(crate) const RTMGRP_IPV4_ROUTE: u32 = 0x40;
+pub(crate) const RTMGRP_IPV4_RULE: u32 = 0x80;
+pub(crate) const RTMGRP_IPV6_IFADDR: u32 = 0x100;
+pub(crate) const RTMGRP_IPV6_ROUTE: u32 = 0x400;
+// There is no RTMGRP_ constant for RTNLGRP_IPV6_RULE (19), but it still fits in the bind mask.
+pub(crate) const RTMGRP_IPV6_RULE: u32 = 1 << (19 - 1);
+// Likewise for RTNLGRP_ND_USEROPT (20).
+pub(crate) const RTMGRP_ND_USEROPT: u32 = 1 << (20 - 1);
+
+/// The interface list, and the DNS configuration if it is being watched too.
+#[derive(Clone)]
+pub(crate) struct InterfaceSnapshot {
+    list: List,
+    dns_options: Option<DnsOptions>,
+    dns: Option<DnsConfig>,
+}
+
+impl InterfaceSnapshot {
+    fn read_dns(&mut self) {
+        self.dns = self
+            .dns_options
+            .as_ref()
+            .map(|options| crate::resolv::dns_config(options).unwrap_or_default());
+    }
+}
+
+impl Snapshot for InterfaceSnapshot {
+    type Update = Update;
+    type Cursor = UpdateCursor;
+
+    fn fetch(options: &WatchOptions) -> Result<Self, Error> {
+        let mut snapshot = Self {
+            list: crate::list::list_interfaces()?,
+            dns_options: options.dns.clone(),
+            dns: None,
+        };
+        snapshot.read_dns();
+        Ok(snapshot)
+    }
+
+    fn cursor(options: &WatchOptions) -> UpdateCursor {
+        UpdateCursor::new(options)
+    }
+
+    fn advance(cursor: &mut UpdateCursor, snapshot: Self) -> Option<Update> {
+        cursor.advance_with_dns(snapshot.list, snapshot.dns)
+    }
+}
 
 pub(crate) fn watch_interfaces_with_callback<F: FnMut(Update) + Send + 'static>(
     callback: F,
+    options: &WatchOptions,
 ) -> Result<WatchHandle, Error> {
-    crate::watch_fd::watch_interfaces_with_callback(callback, EVENT_SOCKET_OPS)
+    crate::watch_fd::watch_with_callback(callback, EVENT_SOCKET_OPS, options)
 }
 
 pub(crate) fn watch_interfaces_async<A: crate::async_adapter::AsyncFdAdapter>(
+    options: &WatchOptions,
 ) -> Result<AsyncWatch, Error> {
-    crate::watch_fd::watch_interfaces_async::<A>(EVENT_SOCKET_OPS)
+    crate::watch_fd::watch_async::<InterfaceSnapshot, A>(EVENT_SOCKET_OPS, options)
+}
+
+pub(crate) fn watch_interfaces_blocking(options: &WatchOptions) -> Result<BlockingWatch, Error> {
+    crate::watch_fd::watch_blocking(EVENT_SOCKET_OPS, options)
 }
 
-pub(crate) fn watch_interfaces_blocking() -> Result<BlockingWatch, Error> {
-    crate::watch_fd::watch_interfaces_blocking(EVENT_SOCKET_OPS)
+// When DNS is watched as well, the netlink socket and inotify are polled through one epoll
+// descriptor, in that order.
+fn open_event_socket(options: &WatchOptions) -> Result<EventSocket, Error> {
+    let netlink = open_netlink_event_socket(
+        options,
+        RTMGRP_LINK | RTMGRP_IPV4_IFADDR | RTMGRP_IPV6_IFADDR,
+    )?;
+    match &options.dns {
+        Some(dns_options) => {
+            let inotify = crate::resolv::open_inotify(dns_options)?;
+            EventSocket::multiplex(vec![netlink, inotify])
+        }
+        None => Ok(netlink.into()),
+    }
 }
 
-pub(crate) fn open_event_socket() -> Result<OwnedFd, Error> {
+/// Open a non-blocking rtnetlink socket subscribed to the given `RTMGRP_*` groups.
+pub(crate) fn open_netlink_event_socket(
+    options: &WatchOptions,
+    groups: u32,
+) -> Result<OwnedFd, Error> {
     let sockfd = socket(
         AddressFamily::Netlink,
         SockType::Raw,
@@ -47,19 +130,63 @@ pub(crate) fn open_event_socket() -> Result<OwnedFd, Error> {
         Some(SockProtocol::NetlinkRoute),
     )
     .map_err(|e| Error::CreateSocket(e.to_string()))?;
-    let sa_nl = NetlinkAddr::new(0, RTMGRP_LINK | RTMGRP_IPV4_IFADDR | RTMGRP_IPV6_IFADDR);
+    if let Some(size) = options.receive_buffer_size {
+        // The forced variant ignores rmem_max but needs CAP_NET_ADMIN.
+        setsockopt(&sockfd, RcvBufForce, &size)
+            .or_else(|_| setsockopt(&sockfd, RcvBuf, &size))
+            .map_err(|e| Error::SetSocketOption(e.to_string()))?;
+    }
+    let sa_nl = NetlinkAddr::new(0, groups);
     bind(sockfd.as_raw_fd(), &sa_nl).map_err(|e| Error::Bind(e.to_string()))?;
     Ok(sockfd)
 }
 
-pub(crate) fn drain_event_socket(fd: BorrowedFd<'_>) {
-    let mut buf = [0u8; 4096];
+fn drain_event_socket(
+    fd: BorrowedFd<'_>,
+    sources: &[OwnedFd],
+    snapshot: &mut InterfaceSnapshot,
+) -> Drained {
+    let [netlink, inotify] = sources else {
+        return drain_netlink(fd, &mut snapshot.list);
+    };
+    if crate::resolv::drain_inotify(inotify.as_fd()) {
+        if let Some(dns_options) = &snapshot.dns_options {
+            crate::resolv::watch_dirs(inotify.as_fd(), dns_options);
+        }
+        snapshot.read_dns();
+    }
+    drain_netlink(netlink.as_fd(), &mut snapshot.list)
+}
+
+fn drain_netlink(fd: BorrowedFd<'_>, list: &mut List) -> Drained {
+    let mut buf = [0u8; 8192];
+    let mut drained = Drained::Applied;
     loop {
-        match recv(fd.as_raw_fd(), &mut buf, MsgFlags::empty()) {
+        match recv(fd.as_raw_fd(), &mut buf, MsgFlags::MSG_TRUNC) {
             Ok(0) => break,
-            Ok(_) => continue,
+            // A truncated datagram has lost notifications
+            Ok(len) if len > buf.len() => drained = drained.max(Drained::Relist),
+            Ok(len) => {
+                // Once a relist is required there is no point applying the rest, but the socket
+                // must still be emptied so that it stops polling readable.
+                if drained == Drained::Applied {
+                    for message in messages(&buf[..len]) {
+                        drained = crate::list::apply_message(list, &message);
+                        if drained == Drained::Relist {
+                            break;
+                        }
+                    }
+                }
+            }
             Err(Errno::EAGAIN) => break,
-            Err(_) => break,
+            // The kernel dropped notifications because the receive buffer was full. The socket
+            // remains usable, so keep draining what was queued after the overrun.
+            Err(Errno::ENOBUFS) => drained = Drained::Overrun,
+            Err(_) => {
+                drained = drained.max(Drained::Relist);
+                break;
+            }
         }
     }
+    drained
 }
@@ -2,35 +2,43 @@ use std::os::fd::{AsRawFd, BorrowedFd, OwnedFd};
 
 use nix::errno::Errno;
 use nix::libc::{fcntl, F_GETFL, F_SETFL, O_NONBLOCK};
-use nix::sys::socket::{recv, socket, AddressFamily, MsgFlags, SockFlag, SockType};
+use nix::sys::socket::sockopt::RcvBuf;
+use nix::sys::socket::{recv, setsockopt, socket, AddressFamily, MsgFlags, SockFlag, SockType};
 
 pub(crate) use crate::watch_fd::{AsyncWatch, BlockingWatch, WatchHandle};
-use crate::{Error, Update};
+use crate::watch_fd::{Drained, EventSocket};
+use crate::{Error, List, Update, WatchOptions};
 
-const EVENT_SOCKET_OPS: crate::watch_fd::EventSocketOps = crate::watch_fd::EventSocketOps {
+const EVENT_SOCKET_OPS: crate::watch_fd::EventSocketOps<List> = crate::watch_fd::EventSocketOps {
     open: open_event_socket,
     drain: drain_event_socket,
 };
 
 pub(crate) fn watch_interfaces_with_callback<F: FnMut(Update) + Send + 'static>(
     callback: F,
+    options: &WatchOptions,
 ) -> Result<WatchHandle, Error> {
-    crate::watch_fd::watch_interfaces_with_callback(callback, EVENT_SOCKET_OPS)
+    crate::watch_fd::watch_with_callback(callback, EVENT_SOCKET_OPS, options)
 }
 
 pub(crate) fn watch_interfaces_async<A: crate::async_adapter::AsyncFdAdapter>(
+    options: &WatchOptions,
 ) -> Result<AsyncWatch, Error> {
-    crate::watch_fd::watch_interfaces_async::<A>(EVENT_SOCKET_OPS)
+    crate::watch_fd::watch_async::<List, A>(EVENT_SOCKET_OPS, options)
 }
 
-pub(crate) fn watch_interfaces_blocking() -> Result<BlockingWatch, Error> {
-    crate::watch_fd::watch_interfaces_blocking(EVENT_SOCKET_OPS)
+pub(crate) fn watch_interfaces_blocking(options: &WatchOptions) -> Result<BlockingWatch, Error> {
+    crate::watch_fd::watch_blocking(EVENT_SOCKET_OPS, options)
 }
 
-pub(crate) fn open_event_socket() -> Result<OwnedFd, Error> {
+pub(crate) fn open_event_socket(options: &WatchOptions) -> Result<EventSocket, Error> {
     let sockfd = socket(AddressFamily::Route, SockType::Raw, SockFlag::empty(), None)
         .map_err(|e| Error::CreateSocket(e.to_string()))?;
 
+    if let Some(size) = options.receive_buffer_size {
+        setsockopt(&sockfd, RcvBuf, &size).map_err(|e| Error::SetSocketOption(e.to_string()))?;
+    }
+
     let flags = unsafe { fcntl(sockfd.as_raw_fd(), F_GETFL) };
     if flags == -1 {
         return Err(Error::CreateSocket(
@@ -45,17 +53,25 @@ pub(crate) fn open_event_socket() -> Result<OwnedFd, Error> {
         ));
     }
 
-    Ok(sockfd)
+    Ok(sockfd.into())
 }
 
-pub(crate) fn drain_event_socket(fd: BorrowedFd<'_>) {
+// Routing socket messages are only used as a wakeup, so the list is always fetched again.
+pub(crate) fn drain_event_socket(
+    fd: BorrowedFd<'_>,
+    _sources: &[OwnedFd],
+    _list: &mut List,
+) -> Drained {
     let mut buf = [0u8; 8192];
+    let mut drained = Drained::Relist;
     loop {
         match recv(fd.as_raw_fd(), &mut buf, MsgFlags::empty()) {
             Ok(0) => break,
             Ok(_) => continue,
             Err(Errno::EAGAIN) => break,
+            Err(Errno::ENOBUFS) => drained = Drained::Overrun,
             Err(_) => break,
         }
     }
+    drained
 }
@@ -26,6 +26,8 @@ use crate::callback::Callback;
 use crate::Error;
 use crate::List;
 use crate::Update;
+use crate::WatchOptions;
+use crate::WatchStats;
 
 struct NotificationHandle(HANDLE);
 
@@ -136,6 +138,12 @@ pub(crate) struct WatchHandle {
     _registration: NotificationRegistration<WatchState>,
 }
 
+impl WatchHandle {
+    pub(crate) fn stats(&self) -> WatchStats {
+        WatchStats::default()
+    }
+}
+
 struct QueuedWatchState {
     current_list: List,
     queue: SharedAsyncCallbackQueue,
@@ -175,6 +183,10 @@ impl AsyncWatch {
             }
         }
     }
+
+    pub(crate) fn stats(&self) -> WatchStats {
+        WatchStats::default()
+    }
 }
 
 impl BlockingWatch {
@@ -186,13 +198,18 @@ impl BlockingWatch {
             }
         }
     }
+
+    pub(crate) fn stats(&self) -> WatchStats {
+        WatchStats::default()
+    }
 }
 
 pub(crate) fn watch_interfaces_with_callback<F: FnMut(Update) + Send + 'static>(
     callback: F,
+    options: &WatchOptions,
 ) -> Result<WatchHandle, Error> {
     let state = Box::pin(Mutex::new(WatchState {
-        cursor: crate::UpdateCursor::default(),
+        cursor: crate::UpdateCursor::new(options),
         callback: Callback::new(Box::new(callback)),
         initialising: true,
         disabled: false,
@@ -211,21 +228,22 @@ pub(crate) fn watch_interfaces_with_callback<F: FnMut(Update) + Send + 'static>(
 
 #[allow(clippy::extra_unused_type_parameters)]
 pub(crate) fn watch_interfaces_async<A: crate::async_adapter::AsyncFdAdapter>(
+    options: &WatchOptions,
 ) -> Result<AsyncWatch, Error> {
     let (registration, queue) = register_queued_watcher()?;
     Ok(AsyncWatch {
         _registration: registration,
         queue,
-        cursor: crate::UpdateCursor::default(),
+        cursor: crate::UpdateCursor::new(options),
     })
 }
 
-pub(crate) fn watch_interfaces_blocking() -> Result<BlockingWatch, Error> {
+pub(crate) fn watch_interfaces_blocking(options: &WatchOptions) -> Result<BlockingWatch, Error> {
     let (registration, queue) = register_queued_watcher()?;
     Ok(BlockingWatch {
         _registration: registration,
         queue,
-        cursor: crate::UpdateCursor::default(),
+        cursor: crate::UpdateCursor::new(options),
     })
 }
 
@@ -387,8 +405,9 @@ mod tests {
             Interface {
                 index: 1,
                 name: name.to_owned(),
-                hw_addr: String::new(),
+                hw_addr: None,
                 ips: Vec::new(),
+                ..Default::default()
             },
         )]))
     }
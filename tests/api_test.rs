@@ -19,6 +19,14 @@ mod helpers;
 #[path = "helpers/ipv6.rs"]
 mod ipv6_helpers;
 
+#[cfg(target_os = "linux")]
+#[path = "helpers/batch_linux.rs"]
+mod batch_helpers;
+
+#[cfg(target_os = "linux")]
+#[path = "helpers/link_linux.rs"]
+mod link_helpers;
+
 #[cfg(windows)]
 #[path = "helpers/windows_interface.rs"]
 mod windows_interface;
@@ -84,12 +92,12 @@ fn assert_is_initial(updates: &Arc<Mutex<Vec<Update>>>, update_index: usize, exp
     assert_eq!(updates_guard[update_index].is_initial, expected);
 }
 
-#[cfg(windows)]
-fn wait_for_matching_update(
-    receiver: &std::sync::mpsc::Receiver<Update>,
+#[cfg(any(windows, target_os = "linux"))]
+fn wait_for_matching_update<T>(
+    receiver: &std::sync::mpsc::Receiver<T>,
     description: &str,
-    matches: impl Fn(&Update) -> bool,
-) -> Update {
+    matches: impl Fn(&T) -> bool,
+) -> T {
     let deadline = std::time::Instant::now() + Duration::from_secs(10);
     loop {
         let remaining = deadline.saturating_duration_since(std::time::Instant::now());
@@ -118,6 +126,35 @@ fn test_list_interfaces_has_loopback() {
     assert!(loopback_found, "address 127.0.0.1/8 not found");
 }
 
+#[test]
+#[cfg(target_os = "linux")]
+fn test_interface_stats_count_loopback_traffic() {
+    use netwatcher::interface_stats;
+    use std::net::UdpSocket;
+
+    let loopback = list_interfaces()
+        .unwrap()
+        .into_values()
+        .find(|interface| interface.flags.loopback)
+        .expect("loopback should be listed");
+    let before = interface_stats().unwrap()[&loopback.index];
+
+    let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+    sender
+        .send_to(&[0; 1000], receiver.local_addr().unwrap())
+        .unwrap();
+    let mut buf = [0; 1000];
+    receiver.recv(&mut buf).unwrap();
+
+    let after = interface_stats().unwrap()[&loopback.index];
+    assert!(after.rx_bytes >= before.rx_bytes + 1000);
+    assert!(after.tx_packets > before.tx_packets);
+    let rates = after.rates_since(&before).unwrap();
+    assert!(rates.rx_bytes > 0.0);
+    assert!(!rates.counters_reset);
+}
+
 #[test]
 #[ignore] // installs a temporary network adapter and requires administrator context
 #[cfg(windows)]
@@ -401,3 +438,992 @@ fn test_watch_interfaces_blocking_loopback_ipv6_changes() {
     assert_update_has_ip(&removed, &expected_original, true);
     assert_update_has_ip(&removed, &expected_added, false);
 }
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(loopback)]
+fn test_watch_interfaces_blocking_overrun_resyncs() {
+    use helpers::sys::discover_loopback_interface;
+    use netwatcher::{watch_interfaces_blocking_with_options, WatchOptions};
+
+    let loopback_interface = discover_loopback_interface();
+    let ips: Vec<String> = (1..=250).map(|i| format!("127.0.1.{i}")).collect();
+
+    // The kernel rounds this up to its minimum, which is still far too small to queue one
+    // notification per address.
+    let options = WatchOptions::default().receive_buffer_size(1);
+    let mut watch =
+        watch_interfaces_blocking_with_options(&options).expect("failed to create watcher");
+    let initial = watch.changed();
+    assert!(initial.is_initial);
+    assert_eq!(watch.stats().overruns, 0);
+
+    batch_helpers::add_ips_to_interface(&loopback_interface, &ips);
+    let added = watch.changed();
+    batch_helpers::remove_ips_from_interface(&loopback_interface, &ips);
+
+    let stats = watch.stats();
+    assert!(stats.overruns >= 1, "expected an overrun, got {stats:?}");
+    assert!(stats.resyncs >= 1, "expected a resync, got {stats:?}");
+    // The resync means the update reflects every address despite the dropped notifications.
+    for ip in &ips {
+        let expected = IpRecord {
+            ip: ip.parse().unwrap(),
+            prefix_len: 8,
+        };
+        helpers::assert_update_has_ip(&added, &expected, true);
+    }
+}
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(veth)]
+fn test_watch_interfaces_include_down_reports_admin_state_changes() {
+    use netwatcher::{
+        list_interfaces_with_options, watch_interfaces_with_callback_and_options, ListOptions,
+        WatchOptions,
+    };
+
+    let veth = link_helpers::VethPair::create("nwtest0", "nwtest1");
+    let find = |interfaces: &std::collections::HashMap<u32, netwatcher::Interface>| {
+        interfaces
+            .values()
+            .find(|interface| interface.name == veth.name())
+            .map(|interface| interface.index)
+    };
+
+    // A freshly created veth is down, so it is only listed on request.
+    assert_eq!(find(&list_interfaces().unwrap()), None);
+    let ifindex =
+        find(&list_interfaces_with_options(&ListOptions::default().include_down(true)).unwrap())
+            .expect("down interface should be listed");
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let options = WatchOptions::default().include_down(true);
+    let _handle = watch_interfaces_with_callback_and_options(
+        move |update| {
+            let _ = tx.send(update);
+        },
+        &options,
+    )
+    .expect("failed to create watcher");
+    let initial = rx.recv().unwrap();
+    assert!(!initial.interfaces[&ifindex].flags.up);
+
+    let admin_state_changed = |update: &Update| {
+        update
+            .diff
+            .modified
+            .get(&ifindex)
+            .is_some_and(|diff| diff.admin_state_changed)
+    };
+
+    link_helpers::set_link_up(veth.name());
+    let up = wait_for_matching_update(&rx, "interface up", admin_state_changed);
+    assert!(up.interfaces[&ifindex].flags.up);
+
+    link_helpers::set_link_down(veth.name());
+    let down = wait_for_matching_update(&rx, "interface down", admin_state_changed);
+    assert!(!down.interfaces[&ifindex].flags.up);
+    assert!(!down.diff.removed.contains_key(&ifindex));
+}
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(veth)]
+fn test_watch_interfaces_reports_mtu_changes() {
+    use netwatcher::{watch_interfaces_with_callback_and_options, WatchOptions};
+
+    let veth = link_helpers::VethPair::create("nwtest0", "nwtest1");
+    let (tx, rx) = std::sync::mpsc::channel();
+    let options = WatchOptions::default().include_down(true);
+    let _handle = watch_interfaces_with_callback_and_options(
+        move |update| {
+            let _ = tx.send(update);
+        },
+        &options,
+    )
+    .expect("failed to create watcher");
+    let initial = rx.recv().unwrap();
+    let interface = initial
+        .interfaces
+        .values()
+        .find(|interface| interface.name == veth.name())
+        .expect("veth should be listed");
+    let ifindex = interface.index;
+    assert_eq!(interface.mtu, Some(1500));
+
+    link_helpers::set_link_mtu(veth.name(), 1400);
+    let update = wait_for_matching_update(&rx, "mtu change", |update| {
+        update
+            .diff
+            .modified
+            .get(&ifindex)
+            .is_some_and(|diff| diff.mtu_changed)
+    });
+    assert_eq!(update.interfaces[&ifindex].mtu, Some(1400));
+}
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(veth)]
+fn test_watch_interfaces_reports_master_and_lower_device() {
+    use netwatcher::{watch_interfaces_with_callback_and_options, InterfaceKind, WatchOptions};
+
+    let veth = link_helpers::VethPair::create("nwtest0", "nwtest1");
+    let bridge = link_helpers::Bridge::create("nwtestbr0");
+    link_helpers::add_alt_name(veth.name(), "nwtest-port");
+    let (tx, rx) = std::sync::mpsc::channel();
+    let options = WatchOptions::default().include_down(true);
+    let _handle = watch_interfaces_with_callback_and_options(
+        move |update| {
+            let _ = tx.send(update);
+        },
+        &options,
+    )
+    .expect("failed to create watcher");
+    let initial = rx.recv().unwrap();
+    let index_of = |name: &str| {
+        initial
+            .interfaces
+            .values()
+            .find(|interface| interface.name == name)
+            .map(|interface| interface.index)
+            .expect("interface should be listed")
+    };
+    let ifindex = index_of(veth.name());
+    let peer_index = index_of("nwtest1");
+    let bridge_index = index_of(bridge.name());
+    assert_eq!(initial.interfaces[&ifindex].link, Some(peer_index));
+    assert_eq!(initial.interfaces[&ifindex].link_netnsid, None);
+    assert_eq!(initial.interfaces[&ifindex].master, None);
+
+    link_helpers::set_link_master(veth.name(), bridge.name());
+    let update = wait_for_matching_update(&rx, "master change", |update| {
+        update
+            .diff
+            .modified
+            .get(&ifindex)
+            .is_some_and(|diff| diff.master_changed)
+    });
+    assert_eq!(update.interfaces[&ifindex].master, Some(bridge_index));
+    // The bridge also sends partial AF_BRIDGE messages about its port, which must not replace
+    // what is known about the interface.
+    let before = &initial.interfaces[&ifindex];
+    let enslaved = &update.interfaces[&ifindex];
+    assert_eq!(enslaved.kind, Some(InterfaceKind::Veth));
+    assert_eq!(enslaved.kind, before.kind);
+    assert_eq!(enslaved.carrier, before.carrier);
+    assert_eq!(enslaved.alt_names, vec!["nwtest-port".to_string()]);
+
+    // Releasing the port sends an AF_BRIDGE RTM_DELLINK, yet the interface itself remains.
+    link_helpers::unset_link_master(veth.name());
+    let update = wait_for_matching_update(&rx, "master removal", |update| {
+        update
+            .diff
+            .modified
+            .get(&ifindex)
+            .is_some_and(|diff| diff.master_changed)
+    });
+    let released = &update.interfaces[&ifindex];
+    assert_eq!(released.master, None);
+    assert_eq!(released.kind, Some(InterfaceKind::Veth));
+    assert_eq!(released.alt_names, vec!["nwtest-port".to_string()]);
+}
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(veth)]
+fn test_list_interfaces_reports_kind() {
+    use netwatcher::{list_interfaces_with_options, InterfaceKind, ListOptions};
+
+    let veth = link_helpers::VethPair::create("nwtest0", "nwtest1");
+    let bridge = link_helpers::Bridge::create("nwtestbr0");
+
+    let interfaces =
+        list_interfaces_with_options(&ListOptions::default().include_down(true)).unwrap();
+    let kind_of = |name: &str| {
+        interfaces
+            .values()
+            .find(|interface| interface.name == name)
+            .and_then(|interface| interface.kind.clone())
+    };
+    assert_eq!(kind_of("lo"), Some(InterfaceKind::Loopback));
+    assert_eq!(kind_of(veth.name()), Some(InterfaceKind::Veth));
+    assert_eq!(kind_of("nwtest1"), Some(InterfaceKind::Veth));
+    assert_eq!(kind_of(bridge.name()), Some(InterfaceKind::Bridge));
+}
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(veth)]
+fn test_watch_interfaces_reports_address_flag_transitions() {
+    use netwatcher::{watch_interfaces_with_callback_and_options, WatchOptions};
+
+    let veth = link_helpers::VethPair::create("nwtest0", "nwtest1");
+    link_helpers::set_link_up("nwtest1");
+    link_helpers::set_link_up(veth.name());
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let _handle = watch_interfaces_with_callback_and_options(
+        move |update| {
+            let _ = tx.send(update);
+        },
+        &WatchOptions::default(),
+    )
+    .expect("failed to create watcher");
+    let initial = rx.recv().unwrap();
+    let ifindex = initial
+        .interfaces
+        .values()
+        .find(|interface| interface.name == veth.name())
+        .expect("veth should be listed")
+        .index;
+
+    let record = IpRecord {
+        ip: "2001:db8::1".parse().unwrap(),
+        prefix_len: 64,
+    };
+    link_helpers::add_addr(veth.name(), "2001:db8::1/64");
+    let added = wait_for_matching_update(&rx, "tentative address", |update| {
+        update
+            .addrs_added()
+            .any(|(index, addr)| index == ifindex && *addr == record)
+    });
+    let flags = added.interfaces[&ifindex].addr_info[&record].flags;
+    assert!(flags.tentative);
+    assert!(flags.permanent);
+
+    // Duplicate address detection completes after about a second.
+    let preferred = wait_for_matching_update(&rx, "preferred address", |update| {
+        update
+            .addrs_modified()
+            .any(|(index, addr)| index == ifindex && *addr == record)
+    });
+    assert!(
+        !preferred.interfaces[&ifindex].addr_info[&record]
+            .flags
+            .tentative
+    );
+}
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(veth)]
+fn test_list_interfaces_reports_address_lifetimes() {
+    use netwatcher::{list_interfaces_with_options, ListOptions};
+    use std::time::{Duration, Instant};
+
+    let veth = link_helpers::VethPair::create("nwtest0", "nwtest1");
+    link_helpers::add_addr("nwtest1", "192.0.2.2/24");
+    link_helpers::add_addr_with_lifetimes(veth.name(), "192.0.2.1/24", 3600, 1800);
+
+    let before = Instant::now();
+    let interfaces =
+        list_interfaces_with_options(&ListOptions::default().include_down(true)).unwrap();
+    let info_for = |name: &str, ip: [u8; 4]| {
+        let interface = interfaces
+            .values()
+            .find(|interface| interface.name == name)
+            .unwrap();
+        interface.addr_info[&IpRecord {
+            ip: IpAddr::V4(Ipv4Addr::from(ip)),
+            prefix_len: 24,
+        }]
+            .clone()
+    };
+
+    let lifetimes = info_for(veth.name(), [192, 0, 2, 1])
+        .lifetimes
+        .expect("address should expire");
+    let valid = lifetimes.valid.unwrap();
+    assert!(valid <= Duration::from_secs(3600) && valid > Duration::from_secs(3500));
+    let preferred = lifetimes.preferred.unwrap();
+    assert!(preferred <= Duration::from_secs(1800) && preferred > Duration::from_secs(1700));
+    assert!(lifetimes.reported_at >= before);
+    assert_eq!(info_for("nwtest1", [192, 0, 2, 2]).lifetimes, None);
+}
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(veth)]
+fn test_list_interfaces_reports_broadcast_and_peer() {
+    use netwatcher::{list_interfaces_with_options, ListOptions};
+
+    let veth = link_helpers::VethPair::create("nwtest0", "nwtest1");
+    link_helpers::add_broadcast_addr(veth.name(), "192.0.2.1/24");
+    link_helpers::add_peer_addr(veth.name(), "198.51.100.1", "198.51.100.2/32");
+    link_helpers::add_peer_addr(veth.name(), "198.51.100.1", "198.51.100.3/32");
+
+    let interfaces =
+        list_interfaces_with_options(&ListOptions::default().include_down(true)).unwrap();
+    let interface = interfaces
+        .values()
+        .find(|interface| interface.name == veth.name())
+        .unwrap();
+
+    let lan = &interface.addr_info[&IpRecord {
+        ip: IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)),
+        prefix_len: 24,
+    }];
+    assert_eq!(
+        lan.broadcast,
+        Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 255)))
+    );
+    assert!(lan.peers.is_empty());
+
+    let tunnel = &interface.addr_info[&IpRecord {
+        ip: IpAddr::V4(Ipv4Addr::new(198, 51, 100, 1)),
+        prefix_len: 32,
+    }];
+    // One local address configured for two peers is a single address with both peers.
+    assert_eq!(
+        tunnel.peers,
+        vec![
+            IpAddr::V4(Ipv4Addr::new(198, 51, 100, 2)),
+            IpAddr::V4(Ipv4Addr::new(198, 51, 100, 3)),
+        ]
+    );
+}
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(veth)]
+fn test_list_interfaces_reports_scope_and_label() {
+    use netwatcher::{list_interfaces_with_options, AddrScope, ListOptions};
+
+    let veth = link_helpers::VethPair::create("nwtest0", "nwtest1");
+    link_helpers::add_labelled_addr(veth.name(), "192.0.2.1/24", "nwtest0:1");
+
+    let interfaces =
+        list_interfaces_with_options(&ListOptions::default().include_down(true)).unwrap();
+    let interface = interfaces
+        .values()
+        .find(|interface| interface.name == veth.name())
+        .unwrap();
+    let alias = &interface.addr_info[&IpRecord {
+        ip: IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)),
+        prefix_len: 24,
+    }];
+    assert_eq!(alias.scope, Some(AddrScope::Global));
+    assert_eq!(alias.label.as_deref(), Some("nwtest0:1"));
+
+    let lo = interfaces
+        .values()
+        .find(|interface| interface.flags.loopback)
+        .unwrap();
+    let localhost = &lo.addr_info[&IpRecord {
+        ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+        prefix_len: 8,
+    }];
+    assert_eq!(localhost.scope, Some(AddrScope::Host));
+    assert_eq!(localhost.label.as_deref(), Some("lo"));
+}
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(veth)]
+fn test_link_details_are_unknown_for_veth() {
+    use netwatcher::{link_details, list_interfaces_with_options, LinkDetails, ListOptions};
+
+    let veth = link_helpers::VethPair::create("nwtest0", "nwtest1");
+    link_helpers::set_link_up("nwtest1");
+    link_helpers::set_link_up(veth.name());
+
+    let ifindex = list_interfaces_with_options(&ListOptions::default().include_down(true))
+        .unwrap()
+        .into_values()
+        .find(|interface| interface.name == veth.name())
+        .unwrap()
+        .index;
+    assert_eq!(link_details().unwrap()[&ifindex], LinkDetails::default());
+}
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(veth)]
+fn test_watch_interfaces_reports_alt_names_and_alias() {
+    use netwatcher::{
+        interface_index_by_name, watch_interfaces_with_callback_and_options, WatchOptions,
+    };
+
+    let veth = link_helpers::VethPair::create("nwtest0", "nwtest1");
+    // The kernel does not announce these changes while the interface is down.
+    link_helpers::set_link_up(veth.name());
+    let (tx, rx) = std::sync::mpsc::channel();
+    let options = WatchOptions::default().include_down(true);
+    let _handle = watch_interfaces_with_callback_and_options(
+        move |update| {
+            let _ = tx.send(update);
+        },
+        &options,
+    )
+    .expect("failed to create watcher");
+    let initial = rx.recv().unwrap();
+    let ifindex = initial
+        .interfaces
+        .values()
+        .find(|interface| interface.name == veth.name())
+        .expect("veth should be listed")
+        .index;
+
+    link_helpers::add_alt_name(veth.name(), "nwtest-uplink");
+    let update = wait_for_matching_update(&rx, "altname change", |update| {
+        update
+            .diff
+            .modified
+            .get(&ifindex)
+            .is_some_and(|diff| diff.alt_names_changed)
+    });
+    assert_eq!(update.interfaces[&ifindex].alt_names, vec!["nwtest-uplink"]);
+    assert_eq!(
+        interface_index_by_name("nwtest-uplink").unwrap(),
+        Some(ifindex)
+    );
+
+    link_helpers::set_link_alias(veth.name(), "test uplink");
+    let update = wait_for_matching_update(&rx, "alias change", |update| {
+        update
+            .diff
+            .modified
+            .get(&ifindex)
+            .is_some_and(|diff| diff.alias_changed)
+    });
+    assert_eq!(
+        update.interfaces[&ifindex].alias.as_deref(),
+        Some("test uplink")
+    );
+}
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(veth)]
+fn test_list_routes_includes_static_route() {
+    use netwatcher::{list_routes, Route, RouteProtocol, RouteType};
+
+    let veth = link_helpers::VethPair::create("nwtest0", "nwtest1");
+    link_helpers::set_link_up("nwtest1");
+    link_helpers::set_link_up(veth.name());
+    link_helpers::add_addr(veth.name(), "203.0.113.1/24");
+    link_helpers::add_route("198.51.100.0/24", "203.0.113.254", veth.name(), 50);
+
+    let routes = list_routes().unwrap();
+    let route = routes
+        .iter()
+        .find(|route| route.destination == IpAddr::V4(Ipv4Addr::new(198, 51, 100, 0)))
+        .expect("static route should be listed");
+    assert_eq!(route.prefix_len, 24);
+    assert_eq!(
+        route.gateway,
+        Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 254)))
+    );
+    assert_eq!(route.metric, Some(50));
+    assert_eq!(route.table, Route::MAIN_TABLE);
+    assert_eq!(route.protocol, RouteProtocol::Boot);
+    assert_eq!(route.kind, RouteType::Unicast);
+
+    let ifindex = route.ifindex.expect("route should have an interface");
+    let prefix_route = routes
+        .iter()
+        .find(|route| {
+            route.destination == IpAddr::V4(Ipv4Addr::new(203, 0, 113, 0))
+                && route.table == Route::MAIN_TABLE
+        })
+        .expect("prefix route should be listed");
+    assert_eq!(prefix_route.ifindex, Some(ifindex));
+    assert_eq!(prefix_route.protocol, RouteProtocol::Kernel);
+    assert_eq!(
+        prefix_route.source,
+        Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 1)))
+    );
+    assert!(routes
+        .iter()
+        .any(|route| route.table == Route::LOCAL_TABLE && route.kind == RouteType::Local));
+}
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(veth)]
+fn test_watch_routes_reports_added_and_removed_route() {
+    use netwatcher::{watch_routes_with_callback, NextHop, RouteUpdate, WatchOptions};
+
+    let destination = IpAddr::V4(Ipv4Addr::new(198, 51, 100, 0));
+    let (sender, receiver) = std::sync::mpsc::channel::<RouteUpdate>();
+
+    let veth = link_helpers::VethPair::create("nwtest0", "nwtest1");
+    link_helpers::set_link_up("nwtest1");
+    link_helpers::set_link_up(veth.name());
+    link_helpers::add_addr(veth.name(), "203.0.113.1/24");
+
+    let handle = watch_routes_with_callback(
+        move |update| {
+            let _ = sender.send(update);
+        },
+        &WatchOptions::default(),
+    )
+    .unwrap();
+    let initial = wait_for_matching_update(&receiver, "initial route update", |update| {
+        update.is_initial
+    });
+    assert!(initial
+        .routes
+        .iter()
+        .all(|route| route.destination != destination));
+
+    link_helpers::add_route("198.51.100.0/24", "203.0.113.254", veth.name(), 50);
+    let added = wait_for_matching_update(&receiver, "route to be added", |update| {
+        update
+            .diff
+            .added
+            .iter()
+            .any(|route| route.destination == destination)
+    });
+    assert!(!added.is_initial);
+
+    // Spreading the route over two next hops, and then reweighting one of them, changes the
+    // same route each time.
+    let nexthop = |gateway: u8, weight: u16| NextHop {
+        gateway: Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, gateway))),
+        ifindex: added.diff.added[0].ifindex.unwrap(),
+        weight,
+    };
+    for weights in [(1, 1), (1, 3)] {
+        let nexthops = [("203.0.113.253", weights.0), ("203.0.113.254", weights.1)];
+        link_helpers::replace_multipath_route("198.51.100.0/24", &nexthops, veth.name(), 50);
+        let expected = vec![
+            nexthop(253, weights.0 as u16),
+            nexthop(254, weights.1 as u16),
+        ];
+        wait_for_matching_update(&receiver, "next hops to change", |update| {
+            update
+                .diff
+                .modified
+                .iter()
+                .any(|modified| modified.after.nexthops == expected)
+        });
+    }
+    // Route notifications are applied as they arrive, without listing the routes again.
+    assert_eq!(handle.stats().resyncs, 0);
+
+    // The kernel flushes the route along with the link without announcing its removal.
+    drop(veth);
+    wait_for_matching_update(&receiver, "route to be removed", |update| {
+        update
+            .diff
+            .removed
+            .iter()
+            .any(|route| route.destination == destination)
+    });
+    assert!(handle.stats().resyncs >= 1);
+}
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(veth)]
+fn test_watch_primary_paths_follows_metrics_and_rules() {
+    use netwatcher::{watch_primary_paths_with_callback, PrimaryPathUpdate, WatchOptions};
+
+    let (sender, receiver) = std::sync::mpsc::channel::<PrimaryPathUpdate>();
+    let gateway_is = |update: &PrimaryPathUpdate, gateway: &str| {
+        update.paths.ipv6.as_ref().is_some_and(|path| {
+            path.interface_name == "nwtest0" && path.gateway == Some(gateway.parse().unwrap())
+        })
+    };
+
+    let veth = link_helpers::VethPair::create("nwtest0", "nwtest1");
+    link_helpers::set_link_up("nwtest1");
+    link_helpers::set_link_up(veth.name());
+    link_helpers::add_addr(veth.name(), "2001:db8:1::1/64");
+
+    let _handle = watch_primary_paths_with_callback(
+        move |update| {
+            let _ = sender.send(update);
+        },
+        &WatchOptions::default(),
+    )
+    .unwrap();
+    let initial = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert!(initial.is_initial);
+
+    link_helpers::add_route("::/0", "2001:db8:1::fe", veth.name(), 1);
+    let update = wait_for_matching_update(&receiver, "lowest metric default route", |update| {
+        gateway_is(update, "2001:db8:1::fe")
+            && update.paths.ipv6.as_ref().unwrap().source == Some("2001:db8:1::1".parse().unwrap())
+    });
+    assert!(update.ipv6_changed);
+
+    // A default route in a table that no rule refers to changes nothing.
+    link_helpers::add_route_to_table("::/0", "2001:db8:1::fd", veth.name(), 100);
+    assert!(receiver.recv_timeout(Duration::from_millis(500)).is_err());
+
+    let rule = link_helpers::RoutingRule::add("-6", 100, 100);
+    wait_for_matching_update(&receiver, "default route from rule table", |update| {
+        gateway_is(update, "2001:db8:1::fd")
+    });
+
+    drop(rule);
+    wait_for_matching_update(&receiver, "rule removal", |update| {
+        gateway_is(update, "2001:db8:1::fe")
+    });
+
+    // A multipath default route still gives a path, through one of its next hops.
+    let nexthops = [("2001:db8:1::fb", 1), ("2001:db8:1::fc", 1)];
+    link_helpers::replace_multipath_route("::/0", &nexthops, veth.name(), 1);
+    wait_for_matching_update(&receiver, "multipath default route", |update| {
+        gateway_is(update, "2001:db8:1::fb") || gateway_is(update, "2001:db8:1::fc")
+    });
+
+    drop(veth);
+    wait_for_matching_update(&receiver, "link removal", |update| {
+        update
+            .paths
+            .ipv6
+            .as_ref()
+            .is_none_or(|path| path.interface_name != "nwtest0")
+    });
+}
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(veth)]
+fn test_route_to_reports_interface_gateway_and_source() {
+    use netwatcher::route_to;
+
+    let veth = link_helpers::VethPair::create("nwtest0", "nwtest1");
+    link_helpers::set_link_up("nwtest1");
+    link_helpers::set_link_up(veth.name());
+    link_helpers::add_addr(veth.name(), "203.0.113.1/24");
+    link_helpers::add_route("198.51.100.0/24", "203.0.113.254", veth.name(), 50);
+    let local = IpRecord {
+        ip: IpAddr::V4(Ipv4Addr::new(203, 0, 113, 1)),
+        prefix_len: 24,
+    };
+
+    let lookup = route_to(IpAddr::V4(Ipv4Addr::new(198, 51, 100, 7)))
+        .unwrap()
+        .expect("destination should be reachable");
+    assert_eq!(lookup.interface.name, "nwtest0");
+    assert_eq!(
+        lookup.gateway,
+        Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 254)))
+    );
+    assert_eq!(lookup.source, Some(local.ip));
+    assert_eq!(lookup.source_record, Some(local.clone()));
+    assert!(lookup.interface.ips.contains(&local));
+
+    let on_link = route_to(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 9)))
+        .unwrap()
+        .unwrap();
+    assert_eq!(on_link.interface.index, lookup.interface.index);
+    assert_eq!(on_link.gateway, None);
+
+    let _unreachable = link_helpers::InterfacelessRoute::add("unreachable", "198.51.100.128/25");
+    assert_eq!(
+        route_to(IpAddr::V4(Ipv4Addr::new(198, 51, 100, 200))).unwrap(),
+        None
+    );
+}
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(veth)]
+fn test_watch_neighbours_reports_changed_hw_addr() {
+    use netwatcher::{
+        list_neighbours, watch_neighbours_with_callback, HardwareAddr, NeighbourState,
+        NeighbourUpdate, WatchOptions,
+    };
+
+    let peer_ip = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 50));
+    let veth = link_helpers::VethPair::create("nwtest0", "nwtest1");
+    link_helpers::set_link_up("nwtest1");
+    link_helpers::set_link_up(veth.name());
+    link_helpers::add_addr(veth.name(), "203.0.113.1/24");
+    link_helpers::set_neighbour(veth.name(), "203.0.113.50", "02:00:00:00:00:50");
+
+    let neighbours = list_neighbours().unwrap();
+    let neighbour = neighbours
+        .iter()
+        .find(|neighbour| neighbour.ip == peer_ip)
+        .expect("static neighbour should be listed");
+    assert_eq!(neighbour.state, NeighbourState::Permanent);
+    assert_eq!(
+        neighbour.hw_addr,
+        Some("02:00:00:00:00:50".parse::<HardwareAddr>().unwrap())
+    );
+    let ifindex = neighbour.ifindex;
+
+    let (sender, receiver) = std::sync::mpsc::channel::<NeighbourUpdate>();
+    let _handle = watch_neighbours_with_callback(
+        move |update| {
+            let _ = sender.send(update);
+        },
+        &WatchOptions::default(),
+    )
+    .unwrap();
+    let initial = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert!(initial.is_initial);
+    assert!(initial.diff.added.contains(neighbour));
+
+    link_helpers::set_neighbour(veth.name(), "203.0.113.50", "02:00:00:00:00:51");
+    let update = wait_for_matching_update(&receiver, "neighbour change", |update| {
+        update
+            .diff
+            .modified
+            .iter()
+            .any(|modified| modified.after.ip == peer_ip)
+    });
+    let modified = update
+        .diff
+        .modified
+        .into_iter()
+        .find(|modified| modified.after.ip == peer_ip)
+        .unwrap();
+    assert_eq!(modified.before, *neighbour);
+    assert_eq!(modified.after.ifindex, ifindex);
+    assert_eq!(
+        modified.after.hw_addr,
+        Some("02:00:00:00:00:51".parse::<HardwareAddr>().unwrap())
+    );
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_watch_interfaces_reports_dns_changes() {
+    use netwatcher::{
+        dns_config_with_options, watch_interfaces_with_callback_and_options, DnsOptions,
+        WatchOptions,
+    };
+
+    let dir = std::env::temp_dir().join(format!("netwatcher-dns-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let resolv_conf = dir.join("resolv.conf");
+    std::fs::write(&resolv_conf, "nameserver 192.0.2.53\nsearch example.com\n").unwrap();
+    let dns_options = DnsOptions::default()
+        .resolv_conf_path(&resolv_conf)
+        .resolved_uplink_path(dir.join("uplink.conf"));
+    let config = dns_config_with_options(&dns_options).unwrap();
+    assert_eq!(
+        config.nameservers,
+        vec![IpAddr::V4(Ipv4Addr::new(192, 0, 2, 53))]
+    );
+
+    let (sender, receiver) = std::sync::mpsc::channel::<Update>();
+    let _handle = watch_interfaces_with_callback_and_options(
+        move |update| {
+            let _ = sender.send(update);
+        },
+        &WatchOptions::default().watch_dns(dns_options),
+    )
+    .unwrap();
+    let initial = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert!(initial.diff.dns_changed);
+    assert_eq!(initial.dns, Some(config));
+
+    // Replace the file the way resolvconf and NetworkManager do.
+    let replacement = dir.join("resolv.conf.tmp");
+    std::fs::write(
+        &replacement,
+        "nameserver 2001:db8::53\nsearch corp.example\noptions edns0\n",
+    )
+    .unwrap();
+    std::fs::rename(&replacement, &resolv_conf).unwrap();
+
+    let update =
+        wait_for_matching_update(&receiver, "DNS change", |update| update.diff.dns_changed);
+    let dns = update.dns.unwrap();
+    assert_eq!(
+        dns.nameservers,
+        vec!["2001:db8::53".parse::<IpAddr>().unwrap()]
+    );
+    assert_eq!(dns.search, vec!["corp.example"]);
+    assert_eq!(dns.options, vec!["edns0"]);
+    assert!(update.diff.added.is_empty());
+    assert!(update.diff.removed.is_empty());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(veth)]
+fn test_watch_rules_reports_added_and_removed_rule() {
+    use netwatcher::{
+        list_rules, watch_rules_with_callback, IpRecord, RuleAction, RuleUpdate, WatchOptions,
+    };
+
+    let source = IpRecord {
+        ip: IpAddr::V4(Ipv4Addr::new(198, 51, 100, 0)),
+        prefix_len: 24,
+    };
+    let is_test_rule = |rule: &netwatcher::Rule| rule.priority == 1234 && !rule.ipv6;
+
+    let (sender, receiver) = std::sync::mpsc::channel::<RuleUpdate>();
+    let _handle = watch_rules_with_callback(
+        move |update| {
+            let _ = sender.send(update);
+        },
+        &WatchOptions::default(),
+    )
+    .unwrap();
+    let initial = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert!(initial.is_initial);
+    assert!(initial
+        .rules
+        .iter()
+        .any(|rule| rule.action == RuleAction::Table(netwatcher::Route::MAIN_TABLE)));
+    assert!(!initial.rules.iter().any(is_test_rule));
+
+    let rule = link_helpers::RoutingRule::add_from("-4", 1234, "198.51.100.0/24", 100);
+    let update = wait_for_matching_update(&receiver, "rule addition", |update| {
+        update.diff.added.iter().any(is_test_rule)
+    });
+    let added = update.diff.added.iter().find(|r| is_test_rule(r)).unwrap();
+    assert_eq!(added.from, Some(source.clone()));
+    assert_eq!(added.to, None);
+    assert_eq!(added.table(), Some(100));
+    assert!(list_rules().unwrap().contains(added));
+
+    drop(rule);
+    wait_for_matching_update(&receiver, "rule removal", |update| {
+        update.diff.removed.iter().any(is_test_rule)
+    });
+    assert!(!list_rules().unwrap().iter().any(is_test_rule));
+}
+
+/// Send a router advertisement carrying the given options out of an interface in another
+/// network namespace. It advertises no default route, so only the options have an effect.
+#[cfg(target_os = "linux")]
+fn send_router_advertisement(namespace: &str, interface_name: &str, options: &[u8]) {
+    use nix::libc;
+    use std::os::fd::AsRawFd;
+
+    let netns = std::fs::File::open(format!("/var/run/netns/{namespace}")).unwrap();
+    let interface_name = std::ffi::CString::new(interface_name).unwrap();
+    // Type, code, checksum (filled in by the kernel), hop limit, flags, router lifetime,
+    // reachable time and retransmission timer.
+    let mut packet = vec![134, 0, 0, 0, 64, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    packet.extend_from_slice(options);
+
+    // Switching namespace only affects the calling thread, so the test thread is left alone.
+    std::thread::spawn(move || unsafe {
+        assert_eq!(libc::setns(netns.as_raw_fd(), libc::CLONE_NEWNET), 0);
+        let fd = libc::socket(libc::AF_INET6, libc::SOCK_RAW, libc::IPPROTO_ICMPV6);
+        assert!(fd >= 0, "{}", std::io::Error::last_os_error());
+        // Neighbour discovery messages are only accepted with the maximum hop limit.
+        let hops: libc::c_int = 255;
+        libc::setsockopt(
+            fd,
+            libc::IPPROTO_IPV6,
+            libc::IPV6_MULTICAST_HOPS,
+            &hops as *const _ as *const libc::c_void,
+            std::mem::size_of_val(&hops) as libc::socklen_t,
+        );
+        let mut dest: libc::sockaddr_in6 = std::mem::zeroed();
+        dest.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+        dest.sin6_addr.s6_addr = "ff02::1".parse::<Ipv6Addr>().unwrap().octets();
+        dest.sin6_scope_id = libc::if_nametoindex(interface_name.as_ptr());
+        let sent = libc::sendto(
+            fd,
+            packet.as_ptr() as *const libc::c_void,
+            packet.len(),
+            0,
+            &dest as *const _ as *const libc::sockaddr,
+            std::mem::size_of_val(&dest) as libc::socklen_t,
+        );
+        let error = std::io::Error::last_os_error();
+        libc::close(fd);
+        assert_eq!(sent, packet.len() as isize, "{error}");
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+#[ignore] // needs to run in root context
+#[cfg(target_os = "linux")]
+#[serial(veth)]
+fn test_watch_ra_dns_reports_advertised_servers_and_search_domains() {
+    use netwatcher::{watch_ra_dns_with_callback, RaDnsUpdate, WatchOptions};
+
+    let veth = link_helpers::VethPair::create("nwtest0", "nwtest1");
+    let namespace = link_helpers::NetNamespace::create("nwtest");
+    namespace.adopt("nwtest1");
+    namespace.ip(&["addr", "add", "fe80::2/64", "dev", "nwtest1", "nodad"]);
+    namespace.ip(&["link", "set", "nwtest1", "up"]);
+    // Accept advertisements even if this host forwards packets.
+    link_helpers::set_sysctl(&format!("net.ipv6.conf.{}.accept_ra", veth.name()), "2");
+    link_helpers::set_link_up(veth.name());
+    let ifindex = list_interfaces()
+        .unwrap()
+        .values()
+        .find(|interface| interface.name == veth.name())
+        .expect("veth should be listed")
+        .index;
+
+    let (sender, receiver) = std::sync::mpsc::channel::<RaDnsUpdate>();
+    let _handle = watch_ra_dns_with_callback(
+        move |update| {
+            let _ = sender.send(update);
+        },
+        &WatchOptions::default(),
+    )
+    .unwrap();
+    let initial = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert!(initial.is_initial);
+    assert!(initial.interfaces.iter().all(|c| c.ifindex != ifindex));
+
+    let server: Ipv6Addr = "2001:db8:1::53".parse().unwrap();
+    let options = |lifetime: u32| {
+        let mut options = vec![25, 3, 0, 0];
+        options.extend(lifetime.to_be_bytes());
+        options.extend(server.octets());
+        options.extend([31, 3, 0, 0]);
+        options.extend(lifetime.to_be_bytes());
+        options.extend(b"\x07example\x04test\x00");
+        options.resize(options.len().next_multiple_of(8), 0);
+        options
+    };
+
+    send_router_advertisement(namespace.name(), "nwtest1", &options(600));
+    let update = wait_for_matching_update(&receiver, "advertised servers", |update| {
+        update.changed.contains(&ifindex)
+    });
+    let config = update
+        .interfaces
+        .iter()
+        .find(|config| config.ifindex == ifindex)
+        .expect("advertised configuration should be reported");
+    assert_eq!(config.servers.len(), 1);
+    assert_eq!(config.servers[0].ip, server);
+    assert_eq!(
+        config.servers[0].lifetime.valid,
+        Some(Duration::from_secs(600))
+    );
+    assert_eq!(config.search.len(), 1);
+    assert_eq!(config.search[0].domain, "example.test");
+
+    // A lifetime of zero withdraws what was advertised.
+    send_router_advertisement(namespace.name(), "nwtest1", &options(0));
+    let update = wait_for_matching_update(&receiver, "withdrawn servers", |update| {
+        update.changed.contains(&ifindex)
+    });
+    assert!(update.interfaces.iter().all(|c| c.ifindex != ifindex));
+}
@@ -0,0 +1,35 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+pub fn add_ips_to_interface(interface_name: &str, ips: &[String]) {
+    println!("adding {} IP addresses to {interface_name}", ips.len());
+    run_ip_batch(
+        ips.iter()
+            .map(|ip| format!("addr add {ip}/8 dev {interface_name}")),
+    );
+}
+
+pub fn remove_ips_from_interface(interface_name: &str, ips: &[String]) {
+    println!("removing {} IP addresses from {interface_name}", ips.len());
+    run_ip_batch(
+        ips.iter()
+            .map(|ip| format!("addr del {ip}/8 dev {interface_name}")),
+    );
+}
+
+fn run_ip_batch(commands: impl Iterator<Item = String>) {
+    let mut child = Command::new("sudo")
+        .args(["ip", "-batch", "-"])
+        .stdin(Stdio::piped())
+        .spawn()
+        .expect("failed to execute ip -batch");
+    let mut stdin = child.stdin.take().unwrap();
+    for command in commands {
+        writeln!(stdin, "{command}").expect("failed to write ip batch command");
+    }
+    drop(stdin);
+    let status = child.wait().expect("failed to wait for ip -batch");
+    if !status.success() {
+        panic!("ip -batch failed: {status}");
+    }
+}
@@ -0,0 +1,369 @@
+use std::process::Command;
+
+/// A veth pair that is deleted again when dropped. Both ends start administratively down.
+pub struct VethPair {
+    name: String,
+}
+
+impl VethPair {
+    pub fn create(name: &str, peer: &str) -> Self {
+        println!("creating veth pair {name} <-> {peer}");
+        // Clean up after an earlier run that did not get to drop its pair.
+        let _ = Command::new("sudo")
+            .args(["ip", "link", "del", name])
+            .output();
+        run_ip(&["link", "add", name, "type", "veth", "peer", "name", peer]);
+        Self {
+            name: name.to_string(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Drop for VethPair {
+    fn drop(&mut self) {
+        println!("deleting veth pair {}", self.name);
+        let _ = Command::new("sudo")
+            .args(["ip", "link", "del", &self.name])
+            .output();
+    }
+}
+
+/// A bridge that is deleted again when dropped.
+pub struct Bridge {
+    name: String,
+}
+
+impl Bridge {
+    pub fn create(name: &str) -> Self {
+        println!("creating bridge {name}");
+        let _ = Command::new("sudo")
+            .args(["ip", "link", "del", name])
+            .output();
+        run_ip(&["link", "add", name, "type", "bridge"]);
+        Self {
+            name: name.to_string(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Drop for Bridge {
+    fn drop(&mut self) {
+        println!("deleting bridge {}", self.name);
+        let _ = Command::new("sudo")
+            .args(["ip", "link", "del", &self.name])
+            .output();
+    }
+}
+
+/// A network namespace that is deleted again when dropped, along with the interfaces in it.
+pub struct NetNamespace {
+    name: String,
+}
+
+impl NetNamespace {
+    pub fn create(name: &str) -> Self {
+        println!("creating network namespace {name}");
+        let _ = Command::new("sudo")
+            .args(["ip", "netns", "del", name])
+            .output();
+        run_ip(&["netns", "add", name]);
+        Self {
+            name: name.to_string(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Move an interface into the namespace.
+    pub fn adopt(&self, interface_name: &str) {
+        println!("moving {interface_name} into {}", self.name);
+        run_ip(&["link", "set", interface_name, "netns", &self.name]);
+    }
+
+    /// Run an `ip` command inside the namespace.
+    pub fn ip(&self, args: &[&str]) {
+        let mut ns_args = vec!["-n", &self.name];
+        ns_args.extend_from_slice(args);
+        run_ip(&ns_args);
+    }
+}
+
+impl Drop for NetNamespace {
+    fn drop(&mut self) {
+        println!("deleting network namespace {}", self.name);
+        let _ = Command::new("sudo")
+            .args(["ip", "netns", "del", &self.name])
+            .output();
+    }
+}
+
+/// A policy routing rule sending all traffic of one family to a table, deleted again when dropped.
+pub struct RoutingRule {
+    family: &'static str,
+    priority: String,
+}
+
+impl RoutingRule {
+    pub fn add(family: &'static str, priority: u32, table: u32) -> Self {
+        println!("adding {family} rule {priority} looking up table {table}");
+        let priority = priority.to_string();
+        run_ip(&[
+            family,
+            "rule",
+            "add",
+            "priority",
+            &priority,
+            "table",
+            &table.to_string(),
+        ]);
+        Self { family, priority }
+    }
+
+    /// Add a rule that only applies to traffic from the given source network.
+    pub fn add_from(family: &'static str, priority: u32, from: &str, table: u32) -> Self {
+        println!("adding {family} rule {priority} from {from} looking up table {table}");
+        let priority = priority.to_string();
+        run_ip(&[
+            family,
+            "rule",
+            "add",
+            "priority",
+            &priority,
+            "from",
+            from,
+            "table",
+            &table.to_string(),
+        ]);
+        Self { family, priority }
+    }
+}
+
+impl Drop for RoutingRule {
+    fn drop(&mut self) {
+        println!("deleting {} rule {}", self.family, self.priority);
+        let _ = Command::new("sudo")
+            .args(["ip", self.family, "rule", "del", "priority", &self.priority])
+            .output();
+    }
+}
+
+/// A route of a type that needs no interface, such as `unreachable`, deleted again when dropped.
+pub struct InterfacelessRoute {
+    kind: &'static str,
+    destination: String,
+}
+
+impl InterfacelessRoute {
+    pub fn add(kind: &'static str, destination: &str) -> Self {
+        println!("adding {kind} route to {destination}");
+        let _ = Command::new("sudo")
+            .args(["ip", "route", "del", kind, destination])
+            .output();
+        run_ip(&["route", "add", kind, destination]);
+        Self {
+            kind,
+            destination: destination.to_string(),
+        }
+    }
+}
+
+impl Drop for InterfacelessRoute {
+    fn drop(&mut self) {
+        println!("deleting {} route to {}", self.kind, self.destination);
+        let _ = Command::new("sudo")
+            .args(["ip", "route", "del", self.kind, &self.destination])
+            .output();
+    }
+}
+
+pub fn set_link_up(interface_name: &str) {
+    println!("setting {interface_name} up");
+    run_ip(&["link", "set", "dev", interface_name, "up"]);
+}
+
+pub fn set_link_down(interface_name: &str) {
+    println!("setting {interface_name} down");
+    run_ip(&["link", "set", "dev", interface_name, "down"]);
+}
+
+pub fn set_link_mtu(interface_name: &str, mtu: u32) {
+    println!("setting {interface_name} mtu to {mtu}");
+    run_ip(&[
+        "link",
+        "set",
+        "dev",
+        interface_name,
+        "mtu",
+        &mtu.to_string(),
+    ]);
+}
+
+pub fn set_link_master(interface_name: &str, master: &str) {
+    println!("enslaving {interface_name} to {master}");
+    run_ip(&["link", "set", interface_name, "master", master]);
+}
+
+pub fn unset_link_master(interface_name: &str) {
+    println!("releasing {interface_name} from its master");
+    run_ip(&["link", "set", interface_name, "nomaster"]);
+}
+
+pub fn add_alt_name(interface_name: &str, alt_name: &str) {
+    println!("adding altname {alt_name} to {interface_name}");
+    run_ip(&[
+        "link",
+        "property",
+        "add",
+        "dev",
+        interface_name,
+        "altname",
+        alt_name,
+    ]);
+}
+
+pub fn set_link_alias(interface_name: &str, alias: &str) {
+    println!("setting alias of {interface_name} to {alias}");
+    run_ip(&["link", "set", interface_name, "alias", alias]);
+}
+
+pub fn add_addr(interface_name: &str, cidr: &str) {
+    println!("adding {cidr} to {interface_name}");
+    run_ip(&["addr", "add", cidr, "dev", interface_name]);
+}
+
+pub fn add_broadcast_addr(interface_name: &str, cidr: &str) {
+    println!("adding {cidr} with broadcast to {interface_name}");
+    run_ip(&["addr", "add", cidr, "brd", "+", "dev", interface_name]);
+}
+
+pub fn add_peer_addr(interface_name: &str, local: &str, peer: &str) {
+    println!("adding {local} with peer {peer} to {interface_name}");
+    run_ip(&["addr", "add", local, "peer", peer, "dev", interface_name]);
+}
+
+pub fn add_labelled_addr(interface_name: &str, cidr: &str, label: &str) {
+    println!("adding {cidr} with label {label} to {interface_name}");
+    run_ip(&["addr", "add", cidr, "dev", interface_name, "label", label]);
+}
+
+pub fn add_addr_with_lifetimes(interface_name: &str, cidr: &str, valid: u32, preferred: u32) {
+    println!("adding {cidr} to {interface_name} with lifetimes {valid}/{preferred}");
+    run_ip(&[
+        "addr",
+        "add",
+        cidr,
+        "dev",
+        interface_name,
+        "valid_lft",
+        &valid.to_string(),
+        "preferred_lft",
+        &preferred.to_string(),
+    ]);
+}
+
+pub fn add_route(destination: &str, gateway: &str, interface_name: &str, metric: u32) {
+    println!("adding route to {destination} via {gateway} dev {interface_name} metric {metric}");
+    run_ip(&[
+        "route",
+        "add",
+        destination,
+        "via",
+        gateway,
+        "dev",
+        interface_name,
+        "metric",
+        &metric.to_string(),
+    ]);
+}
+
+pub fn add_route_to_table(destination: &str, gateway: &str, interface_name: &str, table: u32) {
+    println!("adding route to {destination} via {gateway} dev {interface_name} table {table}");
+    run_ip(&[
+        "route",
+        "add",
+        destination,
+        "via",
+        gateway,
+        "dev",
+        interface_name,
+        "table",
+        &table.to_string(),
+    ]);
+}
+
+pub fn replace_multipath_route(
+    destination: &str,
+    nexthops: &[(&str, u32)],
+    interface_name: &str,
+    metric: u32,
+) {
+    println!("replacing route to {destination} with next hops {nexthops:?} dev {interface_name}");
+    let mut args = vec![
+        "route".to_string(),
+        "replace".to_string(),
+        destination.to_string(),
+        "metric".to_string(),
+        metric.to_string(),
+    ];
+    for (gateway, weight) in nexthops {
+        args.extend(
+            ["nexthop", "via", gateway, "dev", interface_name, "weight"].map(str::to_string),
+        );
+        args.push(weight.to_string());
+    }
+    run_ip(&args.iter().map(String::as_str).collect::<Vec<_>>());
+}
+
+pub fn set_neighbour(interface_name: &str, ip: &str, lladdr: &str) {
+    println!("setting permanent neighbour {ip} at {lladdr} on {interface_name}");
+    run_ip(&[
+        "neigh",
+        "replace",
+        ip,
+        "lladdr",
+        lladdr,
+        "nud",
+        "permanent",
+        "dev",
+        interface_name,
+    ]);
+}
+
+pub fn set_sysctl(key: &str, value: &str) {
+    println!("setting {key} to {value}");
+    let result = Command::new("sudo")
+        .args(["sysctl", "-w", &format!("{key}={value}")])
+        .output()
+        .expect("failed to execute sysctl command");
+    if !result.status.success() {
+        panic!(
+            "sysctl {key} failed: {}",
+            String::from_utf8_lossy(&result.stderr)
+        );
+    }
+}
+
+fn run_ip(args: &[&str]) {
+    let result = Command::new("sudo")
+        .arg("ip")
+        .args(args)
+        .output()
+        .expect("failed to execute ip command");
+    if !result.status.success() {
+        panic!(
+            "ip {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&result.stderr)
+        );
+    }
+}